@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use magi_fuzz::Operation;
+
+fuzz_target!(|operation: Operation| {
+    let _ = magi_fuzz::arithmetic(&operation);
+});
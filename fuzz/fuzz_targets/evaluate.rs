@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use magi_fuzz::Program;
+
+fuzz_target!(|program: Program| {
+    let _ = magi_fuzz::evaluate(&program);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use magi_fuzz::Patterns;
+
+fuzz_target!(|patterns: Patterns| {
+    magi_fuzz::linearize(&patterns);
+});
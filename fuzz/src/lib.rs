@@ -0,0 +1,924 @@
+//! Inputs and checks shared by the fuzz targets and their regression tests.
+//!
+//! The inputs are small descriptions of programs and patterns which `arbitrary` can
+//! generate. They are turned into the expressions and patterns the interpreter works
+//! with and then run with a recursion limit and a step budget, so every input has to
+//! finish without panicking.
+//!
+//! Expressions and patterns from the parser are generated as deep as the input describes,
+//! since the library rejects trees nested more than `MAX_NESTING` levels deep. Runtime-only
+//! nodes, patterns and values are cut off at `MAX_DEPTH`, because the library leaves the
+//! depth of trees an embedder builds directly up to the embedder.
+
+use arbitrary::Arbitrary;
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::collections::BTreeMap;
+
+use magc::types::{
+    Block,
+    Call,
+    CaseArm,
+    Conditional,
+    Expression,
+    ExpressionKind,
+    Literal,
+    MatchExpression,
+    Method,
+    Prefix,
+    ReturnExpression,
+    Token,
+    TokenKind,
+    VarDeclaration,
+};
+use magc::types as ast;
+
+use magi::interpreter::{Interpreter, InterpreterError, InterpreterOptions};
+use magi::interpreter::diagnostic::Diagnostic;
+use magi::interpreter::loader::MemoryLoader;
+use magi::types::obj::{compare, MAX_NESTING};
+use magi::types::{
+    AlternativePattern,
+    Assignment,
+    Definition,
+    ErrorValue,
+    FieldPattern,
+    Import,
+    ListPattern,
+    Match,
+    MatchArm,
+    NamedPattern,
+    Obj,
+    ObjKind,
+    PairPattern,
+    Pattern,
+    RangePattern,
+    RestPattern,
+    Sequence,
+    Source,
+    Throw,
+    Try,
+    TuplePattern,
+    ValuePattern,
+    VariablePattern,
+};
+
+/// How deeply generated runtime-only nodes, patterns and values may be nested.
+pub const MAX_DEPTH: usize = 32;
+/// How many levels a nested expression or pattern from the parser may wrap around its leaf.
+///
+/// This is well past `MAX_NESTING`. Deeper trees would only be cloned and dropped, which isn't bounded.
+pub const MAX_GENERATED_NESTING: usize = 4 * MAX_NESTING;
+/// The recursion limit of the interpreter used for fuzzing.
+pub const RECURSION_LIMIT: usize = 256;
+/// The step limit of the interpreter used for fuzzing.
+pub const STEP_LIMIT: usize = 10_000;
+
+/// A few names, so generated programs often refer to the same variables and methods.
+const NAMES: &[&str] = &["a", "b", "f", "x", "kind", "message", "payload", "mod.a"];
+const TYPES: &[&str] = &["Int", "UInt", "Float", "String", "Boolean", "List", "Error", "Nothing"];
+
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Name(u8);
+
+#[derive(Debug, Clone, Arbitrary)]
+pub struct TypeName(u8);
+
+/// A list of top-level expressions which are evaluated one after another.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Program {
+    pub nodes: Vec<Node>,
+}
+
+/// An expression which only exists at runtime, or one coming from the parser.
+#[derive(Debug, Clone, Arbitrary)]
+pub enum Node {
+    Expression(Expr),
+    Value(Value),
+    Pattern(RuntimePattern),
+    Match(Box<Node>, Vec<Arm>),
+    Definition(RuntimePattern, Box<Node>, bool),
+    Assignment(Name, Box<Node>),
+    Sequence(Vec<Node>),
+    Throw(Box<Node>),
+    Try(Box<Node>, Vec<Arm>),
+    Import(Name),
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Arm {
+    pub pattern: RuntimePattern,
+    pub body:    Node,
+}
+
+/// An expression as the parser would produce it, including malformed literals.
+#[derive(Debug, Clone, Arbitrary)]
+pub enum Expr {
+    Literal(LiteralKind, String),
+    Int(i64),
+    Boolean(bool),
+    String(String),
+    Type(TypeName),
+    List(Option<Box<Expr>>),
+    Pattern(Pat),
+    Call(Name, Option<Pat>),
+    Method(Name, Option<Pat>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Prefix(Box<Expr>),
+    Block(Vec<Expr>),
+    Var(Name, Box<Expr>),
+    Return(Box<Expr>),
+    Match(Box<Expr>, Vec<Case>, Option<Box<Expr>>),
+    /// A literal inside this many expressions of one kind, which builds a deeper tree than the input could describe.
+    Nested(Wrapper, u16),
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Case {
+    pub pattern: Pat,
+    pub body:    Expr,
+}
+
+/// The kinds of expressions a nested expression is wrapped in.
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum Wrapper {
+    List,
+    Prefix,
+    Block,
+    Var,
+    Return,
+    Match,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum LiteralKind {
+    Int,
+    Float,
+    String,
+    Boolean,
+}
+
+/// A pattern as the parser would produce it.
+#[derive(Debug, Clone, Arbitrary)]
+pub enum Pat {
+    Variable(Option<Name>, Option<TypeName>),
+    Field(Name, Box<Pat>),
+    Pair(Box<Pat>, Box<Pat>),
+    Tuple(Box<Pat>),
+    Value(Box<Expr>),
+    /// A wildcard inside this many tuple patterns.
+    Nested(u16),
+}
+
+/// Any pattern the interpreter can match with, including ones the parser can't produce.
+#[derive(Debug, Clone, Arbitrary)]
+pub enum RuntimePattern {
+    Variable(Option<Name>, Option<TypeName>),
+    Value(Value),
+    Field(Name, Box<RuntimePattern>, Option<Value>),
+    Pair(Box<RuntimePattern>, Box<RuntimePattern>),
+    Tuple(Box<RuntimePattern>),
+    List(Option<Box<RuntimePattern>>),
+    Rest(Option<Name>),
+    Named(Name, Box<RuntimePattern>),
+    Range(Option<Value>, Option<Value>, bool),
+    Alternative(Vec<RuntimePattern>),
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+pub enum Value {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    /// A float with a lexeme which may not be a number at all.
+    FloatLexeme(String),
+    Boolean(bool),
+    String(String),
+    Nothing,
+    List(Vec<Value>),
+    Error(Name, String, Box<Value>),
+}
+
+/// A reference pattern, a given pattern and some arms to check for exhaustiveness.
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Patterns {
+    pub reference: RuntimePattern,
+    pub given:     RuntimePattern,
+    pub arms:      Vec<RuntimePattern>,
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+pub struct Operation {
+    pub operator: Operator,
+    pub left:     Value,
+    pub right:    Value,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Create an interpreter which can't touch the file system and gives up on long-running programs.
+pub fn interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::with_options(InterpreterOptions {
+        recursion_limit: RECURSION_LIMIT,
+        step_limit:      Some(STEP_LIMIT),
+        prelude:         false,
+        ..InterpreterOptions::default()
+    });
+
+    interpreter.loader = Box::new(MemoryLoader::new());
+    interpreter
+}
+
+/// Evaluate every expression of a program and check that the interpreter is left in a clean state.
+pub fn evaluate(program: &Program) -> Vec<Result<Obj, InterpreterError>> {
+    self::evaluate_with(&mut self::interpreter(), program)
+}
+
+/// Evaluate a program with the given interpreter and return the result of each top-level expression.
+pub fn evaluate_with(interpreter: &mut Interpreter, program: &Program) -> Vec<Result<Obj, InterpreterError>> {
+    let mut results = vec![];
+
+    for node in &program.nodes {
+        // Top-level expressions from the parser go through the same conversion as in a real program.
+        let result = match node {
+            Node::Expression(expr) => {
+                let mut text = SourceText::default();
+                let expression = expr.to_expression(&mut text);
+
+                let result = interpreter.evaluate_expr(Box::new(expression), &text.source(), None);
+                let rejected = matches!(&result, Err(error) if *error.inner() == InterpreterError::TooDeeplyNested);
+
+                assert_eq!(rejected, expr.depth() > MAX_NESTING, "a tree {} levels deep was not checked correctly", expr.depth());
+
+                result
+            },
+
+            node => {
+                let obj = node.to_obj(0);
+                let _ = obj.to_string();
+
+                interpreter.evaluate(Box::new(obj), None)
+            },
+        };
+
+        match &result {
+            Ok(value)  => { let _ = value.to_string(); },
+            Err(error) => self::report(error),
+        }
+
+        assert_eq!(interpreter.recursion_level, 0, "the recursion level was not restored");
+        assert!(interpreter.scopes.is_empty(), "local scopes were left behind");
+        assert!(interpreter.call_stack.is_empty(), "frames were left on the call stack");
+        assert!(interpreter.loading.is_empty(), "modules were left in the loading stack");
+
+        if let Some(limit) = interpreter.options.step_limit {
+            assert!(interpreter.steps <= limit + 1, "the step limit was not enforced");
+        }
+
+        results.push(result.map(|value| *value));
+    }
+
+    results
+}
+
+/// Match two patterns against each other and check the arms of a match expression.
+pub fn linearize(patterns: &Patterns) {
+    let reference = patterns.reference.to_pattern(0);
+    let given = patterns.given.to_pattern(0);
+
+    let _ = reference.to_string();
+    let _ = reference.check_bindings();
+    let _ = reference.defaults();
+    let _ = reference.is_optional();
+    let _ = reference.linearize(given.clone());
+    let _ = given.linearize(reference);
+
+    let arms = patterns.arms
+        .iter()
+        .map(|pattern| MatchArm {
+            pattern: pattern.to_pattern(0),
+            body:    Box::new(Obj::new(ObjKind::Nothing)),
+        })
+        .collect();
+
+    let _ = Match::new(Box::new(Obj::new(ObjKind::Pattern(given))), arms).check();
+}
+
+/// Apply an arithmetic operator, compare its operands and return the result.
+pub fn arithmetic(operation: &Operation) -> Result<Obj, InterpreterError> {
+    let left = operation.left.to_obj(0);
+    let right = operation.right.to_obj(0);
+
+    let _ = compare(&left, &right);
+
+    let result = match operation.operator {
+        Operator::Add => left + right,
+        Operator::Sub => left - right,
+        Operator::Mul => left * right,
+        Operator::Div => left / right,
+    };
+
+    match &result {
+        Ok(value)  => { let _ = value.to_string(); },
+        Err(error) => self::report(error),
+    }
+
+    result
+}
+
+/// Turn an error into everything it can be shown as.
+fn report(error: &InterpreterError) {
+    let _ = ErrorValue::from(error);
+    let _ = Diagnostic::new(error, "<fuzz>", "").to_string();
+}
+
+impl Name {
+    fn as_str(&self) -> &'static str {
+        NAMES[self.0 as usize % NAMES.len()]
+    }
+}
+
+impl TypeName {
+    fn as_str(&self) -> &'static str {
+        TYPES[self.0 as usize % TYPES.len()]
+    }
+}
+
+impl Node {
+    pub fn to_obj(&self, depth: usize) -> Obj {
+        if depth >= MAX_DEPTH {
+            return Obj::new(ObjKind::Nothing)
+        }
+
+        let depth = depth + 1;
+        let arms = |arms: &[Arm]| -> Vec<MatchArm> {
+            arms.iter()
+                .map(|arm| MatchArm {
+                    pattern: arm.pattern.to_pattern(depth),
+                    body:    Box::new(arm.body.to_obj(depth)),
+                })
+                .collect()
+        };
+
+        let kind = match self {
+            Node::Expression(expr)    => {
+                let mut text = SourceText::default();
+                let expression = expr.to_expression(&mut text);
+
+                return self::expression_obj(expression, &text.source())
+            },
+            Node::Value(value)        => return value.to_obj(depth),
+            Node::Pattern(pattern)    => ObjKind::Pattern(pattern.to_pattern(depth)),
+            Node::Match(value, items) => ObjKind::Match(Match::new(Box::new(value.to_obj(depth)), arms(items.as_slice()))),
+
+            Node::Definition(pattern, value, mutable) => {
+                let (pattern, value) = (pattern.to_pattern(depth), Box::new(value.to_obj(depth)));
+
+                ObjKind::Definition(if *mutable { Definition::mutable(pattern, value) } else { Definition::new(pattern, value) })
+            },
+
+            Node::Assignment(name, value) => {
+                ObjKind::Assignment(Assignment::new(name.as_str().to_string(), Box::new(value.to_obj(depth))))
+            },
+
+            Node::Sequence(nodes) => {
+                ObjKind::Sequence(Sequence::new(nodes.iter().map(|node| node.to_obj(depth)).collect()))
+            },
+
+            Node::Throw(value)      => ObjKind::Throw(Throw::new(Box::new(value.to_obj(depth)))),
+            Node::Try(body, items)  => ObjKind::Try(Try::new(Box::new(body.to_obj(depth)), arms(items.as_slice()))),
+            Node::Import(name)      => ObjKind::Import(Import::qualified(name.as_str().to_string(), None)),
+        };
+
+        Obj::new(kind)
+    }
+}
+
+/// Convert an expression the way the interpreter does, keeping malformed literals as raw expressions.
+///
+/// Trees which are nested too deeply are replaced with `nothing`, because wrapping them
+/// into an object directly would bypass the library's check of their depth.
+fn expression_obj(expression: Expression, source: &Source) -> Obj {
+    match Obj::from_expression(expression.clone(), source) {
+        Ok(obj)                                    => obj,
+        Err(InterpreterError::TooDeeplyNested)     => Obj::new(ObjKind::Nothing),
+        Err(_)                                     => Obj::new(ObjKind::Expression(expression, source.clone())),
+    }
+}
+
+/// The source code of a generated expression, made up of the text of its literals.
+///
+/// `magc` expressions only store their position in the source, so every literal is
+/// appended to the text and gets the position it ends up at. A control character
+/// between literals keeps them from being joined into a single grapheme.
+#[derive(Debug, Default)]
+pub struct SourceText {
+    text:      String,
+    graphemes: usize,
+}
+
+impl SourceText {
+    /// Append a lexeme to the text and return an expression which was parsed from it.
+    fn expression(&mut self, kind: ExpressionKind, lexeme: &str) -> Expression {
+        let (start_pos, end_pos) = self.append(lexeme);
+
+        Expression { kind, start_pos, end_pos }
+    }
+
+    /// Append a lexeme to the text and return a token which was read from it.
+    fn token(&mut self, kind: TokenKind, lexeme: &str) -> Token {
+        let (start_pos, end_pos) = self.append(lexeme);
+
+        Token { kind, start_pos, end_pos, line: 1 }
+    }
+
+    /// Append a lexeme to the text and return the positions it starts and ends at.
+    fn append(&mut self, lexeme: &str) -> (usize, usize) {
+        let start_pos = self.graphemes;
+        let end_pos   = start_pos + lexeme.graphemes(true).count();
+
+        self.text.push_str(lexeme);
+        self.text.push('\0');
+        self.graphemes = end_pos + 1;
+
+        (start_pos, end_pos)
+    }
+
+    pub fn source(&self) -> Source {
+        Source::new(&self.text)
+    }
+}
+
+impl Expr {
+    pub fn to_expression(&self, text: &mut SourceText) -> Expression {
+        match self {
+            Expr::Literal(kind, lexeme) => {
+                let literal = match kind {
+                    LiteralKind::Int     => Literal::Int,
+                    LiteralKind::Float   => Literal::Float,
+                    LiteralKind::String  => Literal::String,
+                    LiteralKind::Boolean => Literal::Boolean,
+                };
+
+                text.expression(ExpressionKind::Literal(literal), lexeme)
+            },
+
+            Expr::Int(int)         => text.expression(ExpressionKind::Literal(Literal::Int), &int.to_string()),
+            Expr::Boolean(boolean) => text.expression(ExpressionKind::Literal(Literal::Boolean), &boolean.to_string()),
+            Expr::String(string)   => text.expression(ExpressionKind::Literal(Literal::String), &format!("\"{}\"", string)),
+            Expr::Type(type_id)    => text.expression(ExpressionKind::Type(type_id.as_str().to_string()), type_id.as_str()),
+
+            Expr::List(child) => {
+                let child = child.as_ref().map(|child| Box::new(child.to_expression(text)));
+
+                text.expression(ExpressionKind::List(child), "[]")
+            },
+
+            Expr::Pattern(pattern) => {
+                let pattern = pattern.to_pattern(text);
+
+                text.expression(ExpressionKind::Pattern(pattern), "")
+            },
+
+            Expr::Call(name, signature) => {
+                let call = Call {
+                    name:      name.as_str().to_string(),
+                    signature: signature.as_ref().map(|pattern| pattern.to_pattern(text)),
+                };
+
+                text.expression(ExpressionKind::Call(call), name.as_str())
+            },
+
+            Expr::Method(name, signature, body) => {
+                let method = Method {
+                    name:      name.as_str().to_string(),
+                    signature: signature.as_ref().map(|pattern| pattern.to_pattern(text)),
+                    body:      Box::new(body.to_expression(text)),
+                };
+
+                text.expression(ExpressionKind::Method(method), "def")
+            },
+
+            Expr::Conditional(condition, then_arm, else_arm) => {
+                let conditional = Conditional {
+                    condition: Box::new(condition.to_expression(text)),
+                    then_arm:  Box::new(then_arm.to_expression(text)),
+                    else_arm:  else_arm.as_ref().map(|else_arm| Box::new(else_arm.to_expression(text))),
+                };
+
+                text.expression(ExpressionKind::Conditional(conditional), "if")
+            },
+
+            Expr::Prefix(operand) => {
+                let operator = text.token(TokenKind::Minus, "-");
+                let prefix = Prefix { operator, operand: Box::new(operand.to_expression(text)) };
+
+                text.expression(ExpressionKind::Prefix(prefix), "")
+            },
+
+            Expr::Block(children) => {
+                let block = Block {
+                    environment: BTreeMap::new(),
+                    children:    children.iter().map(|child| child.to_expression(text)).collect(),
+                };
+
+                text.expression(ExpressionKind::Block(block), "do")
+            },
+
+            Expr::Var(name, value) => {
+                let var = VarDeclaration { name: name.as_str().to_string(), value: Box::new(value.to_expression(text)) };
+
+                text.expression(ExpressionKind::Var(var), "var")
+            },
+
+            Expr::Return(value) => {
+                let value = Box::new(value.to_expression(text));
+
+                text.expression(ExpressionKind::Return(ReturnExpression { value }), "return")
+            },
+
+            Expr::Match(subject, cases, else_arm) => {
+                let match_expression = MatchExpression {
+                    subject:  Box::new(subject.to_expression(text)),
+                    arms:     cases.iter().map(|case| CaseArm {
+                        pattern: case.pattern.to_pattern(text),
+                        body:    Box::new(case.body.to_expression(text)),
+                    }).collect(),
+                    else_arm: else_arm.as_ref().map(|else_arm| Box::new(else_arm.to_expression(text))),
+                };
+
+                text.expression(ExpressionKind::Match(match_expression), "match")
+            },
+
+            Expr::Nested(wrapper, levels) => (0..*levels as usize % MAX_GENERATED_NESTING)
+                .fold(Expr::Int(1).to_expression(text), |child, _| wrapper.wrap(child, text)),
+        }
+    }
+}
+
+impl Expr {
+    /// Return how many levels deep the expression is nested, counting both expressions and patterns.
+    pub fn depth(&self) -> usize {
+        let children = match self {
+            Expr::Literal(..) | Expr::Int(_) | Expr::Boolean(_) | Expr::String(_) | Expr::Type(_) => 0,
+
+            Expr::List(child)               => child.as_ref().map_or(0, |child| child.depth()),
+            Expr::Pattern(pattern)          => pattern.depth(),
+            Expr::Call(_, signature)        => signature.as_ref().map_or(0, Pat::depth),
+            Expr::Prefix(operand)           => operand.depth(),
+            Expr::Var(_, value)             => value.depth(),
+            Expr::Return(value)             => value.depth(),
+            Expr::Block(children)           => children.iter().map(Expr::depth).max().unwrap_or(0),
+            Expr::Nested(_, levels)         => *levels as usize % MAX_GENERATED_NESTING,
+
+            Expr::Method(_, signature, body) => signature.as_ref().map_or(0, Pat::depth).max(body.depth()),
+
+            Expr::Conditional(condition, then_arm, else_arm) => {
+                condition.depth().max(then_arm.depth()).max(else_arm.as_ref().map_or(0, |else_arm| else_arm.depth()))
+            },
+
+            Expr::Match(subject, cases, else_arm) => cases
+                .iter()
+                .map(|case| case.pattern.depth().max(case.body.depth()))
+                .chain([subject.depth(), else_arm.as_ref().map_or(0, |else_arm| else_arm.depth())])
+                .max()
+                .unwrap_or(0),
+        };
+
+        children + 1
+    }
+}
+
+impl Wrapper {
+    /// Wrap an expression into one of this kind.
+    fn wrap(&self, child: Expression, text: &mut SourceText) -> Expression {
+        let child = Box::new(child);
+
+        match self {
+            Wrapper::List   => text.expression(ExpressionKind::List(Some(child)), "[]"),
+            Wrapper::Return => text.expression(ExpressionKind::Return(ReturnExpression { value: child }), "return"),
+
+            Wrapper::Prefix => {
+                let operator = text.token(TokenKind::Minus, "-");
+
+                text.expression(ExpressionKind::Prefix(Prefix { operator, operand: child }), "")
+            },
+
+            Wrapper::Block => {
+                let block = Block { environment: BTreeMap::new(), children: vec![*child] };
+
+                text.expression(ExpressionKind::Block(block), "do")
+            },
+
+            Wrapper::Var => {
+                let var = VarDeclaration { name: String::from("x"), value: child };
+
+                text.expression(ExpressionKind::Var(var), "var")
+            },
+
+            Wrapper::Match => {
+                let match_expression = MatchExpression { subject: child, arms: vec![], else_arm: None };
+
+                text.expression(ExpressionKind::Match(match_expression), "match")
+            },
+        }
+    }
+}
+
+impl Pat {
+    /// Return how many levels deep the pattern is nested, counting both expressions and patterns.
+    pub fn depth(&self) -> usize {
+        let children = match self {
+            Pat::Variable(..)       => 0,
+            Pat::Field(_, value)    => value.depth(),
+            Pat::Pair(left, right)  => left.depth().max(right.depth()),
+            Pat::Tuple(child)       => child.depth(),
+            Pat::Value(expr)        => expr.depth(),
+            Pat::Nested(levels)     => *levels as usize % MAX_GENERATED_NESTING,
+        };
+
+        children + 1
+    }
+
+    pub fn to_pattern(&self, text: &mut SourceText) -> ast::Pattern {
+        match self {
+            Pat::Variable(name, type_id) => ast::Pattern::Variable(ast::VariablePattern {
+                name:    name.as_ref().map(|name| name.as_str().to_string()),
+                type_id: type_id.as_ref().map(|type_id| type_id.as_str().to_string()),
+            }),
+
+            Pat::Field(name, value) => ast::Pattern::Record(ast::RecordPattern {
+                name:  name.as_str().to_string(),
+                value: Box::new(value.to_pattern(text)),
+            }),
+
+            Pat::Pair(left, right) => ast::Pattern::Pair(ast::PairPattern {
+                left:  Box::new(left.to_pattern(text)),
+                right: Box::new(right.to_pattern(text)),
+            }),
+
+            Pat::Tuple(child) => ast::Pattern::Tuple(ast::TuplePattern {
+                child: Box::new(child.to_pattern(text)),
+            }),
+
+            Pat::Value(expr) => ast::Pattern::Value(ast::ValuePattern {
+                expression: Box::new(expr.to_expression(text)),
+            }),
+
+            Pat::Nested(levels) => (0..*levels as usize % MAX_GENERATED_NESTING)
+                .fold(Pat::Variable(None, None).to_pattern(text), |child, _| {
+                    ast::Pattern::Tuple(ast::TuplePattern { child: Box::new(child) })
+                }),
+        }
+    }
+}
+
+impl RuntimePattern {
+    pub fn to_pattern(&self, depth: usize) -> Pattern {
+        if depth >= MAX_DEPTH {
+            return Pattern::Variable(VariablePattern { name: None, type_id: None })
+        }
+
+        let depth = depth + 1;
+        let boxed = |pattern: &RuntimePattern| Box::new(pattern.to_pattern(depth));
+        let value = |value: &Value| Box::new(value.to_obj(depth));
+
+        match self {
+            RuntimePattern::Variable(name, type_id) => Pattern::Variable(VariablePattern {
+                name:    name.as_ref().map(|name| name.as_str().to_string()),
+                type_id: type_id.as_ref().map(|type_id| type_id.as_str().to_string()),
+            }),
+
+            RuntimePattern::Value(obj) => Pattern::Value(ValuePattern { obj: value(obj) }),
+
+            RuntimePattern::Field(name, pattern, default) => Pattern::Field(FieldPattern {
+                name:    name.as_str().to_string(),
+                value:   boxed(pattern),
+                default: default.as_ref().map(value),
+            }),
+
+            RuntimePattern::Pair(left, right) => Pattern::Pair(PairPattern { left: boxed(left), right: boxed(right) }),
+            RuntimePattern::Tuple(child)      => Pattern::Tuple(TuplePattern { child: boxed(child) }),
+            RuntimePattern::List(child)       => Pattern::List(ListPattern { child: child.as_deref().map(boxed) }),
+
+            RuntimePattern::Rest(name) => Pattern::Rest(RestPattern {
+                name: name.as_ref().map(|name| name.as_str().to_string()),
+            }),
+
+            RuntimePattern::Named(name, pattern) => Pattern::Named(NamedPattern {
+                name:    name.as_str().to_string(),
+                pattern: boxed(pattern),
+            }),
+
+            RuntimePattern::Range(start, end, inclusive) => Pattern::Range(RangePattern {
+                start:     start.as_ref().map(value),
+                end:       end.as_ref().map(value),
+                inclusive: *inclusive,
+            }),
+
+            // Alternatives with inconsistent bindings are rejected when they are built, so use a wildcard instead.
+            RuntimePattern::Alternative(branches) => {
+                match AlternativePattern::new(branches.iter().map(|branch| branch.to_pattern(depth)).collect()) {
+                    Ok(alternative) => Pattern::Alternative(alternative),
+                    Err(_)          => Pattern::Variable(VariablePattern { name: None, type_id: None }),
+                }
+            },
+        }
+    }
+}
+
+impl Value {
+    pub fn to_obj(&self, depth: usize) -> Obj {
+        if depth >= MAX_DEPTH {
+            return Obj::new(ObjKind::Nothing)
+        }
+
+        let depth = depth + 1;
+
+        let kind = match self {
+            Value::Int(int)            => ObjKind::Int(*int),
+            Value::UInt(uint)          => ObjKind::UInt(*uint),
+            Value::Float(float)        => ObjKind::Float(float.to_string()),
+            Value::FloatLexeme(lexeme) => ObjKind::Float(lexeme.clone()),
+            Value::Boolean(boolean)    => ObjKind::Boolean(*boolean),
+            Value::String(string)      => ObjKind::String(string.clone()),
+            Value::Nothing             => ObjKind::Nothing,
+
+            Value::List(items) => return Obj::list(items.iter().map(|item| item.to_obj(depth)).collect()),
+
+            Value::Error(kind, message, payload) => ObjKind::Error(ErrorValue::new(
+                kind.as_str().to_string(),
+                message.clone(),
+                Box::new(payload.to_obj(depth)),
+            )),
+        };
+
+        Obj::new(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arbitrary::Unstructured;
+
+    /// Run every input which once made a target fail, stored in `regressions/<target>/`.
+    ///
+    /// A missing or empty directory fails, so the saved inputs can't silently stop running.
+    fn replay<T: for<'a> Arbitrary<'a>, R>(target: &str, run: fn(&T) -> R) {
+        let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("regressions").join(target);
+
+        let entries = std::fs::read_dir(&directory)
+            .unwrap_or_else(|e| panic!("can't read the saved inputs in {}: {}", directory.display(), e));
+
+        let mut count = 0;
+
+        for entry in entries {
+            let path = entry.unwrap().path();
+            let bytes = std::fs::read(&path).unwrap();
+
+            let input = T::arbitrary_take_rest(Unstructured::new(&bytes))
+                .unwrap_or_else(|e| panic!("{} is not a valid input: {}", path.display(), e));
+
+            run(&input);
+            count += 1;
+        }
+
+        assert!(count > 0, "there are no saved inputs in {}", directory.display());
+    }
+
+    #[test]
+    fn saved_inputs_still_pass() {
+        replay("evaluate", evaluate);
+        replay("linearize", linearize);
+        replay("arithmetic", arithmetic);
+    }
+
+    fn operate(operator: Operator, left: Value, right: Value) -> Result<ObjKind, InterpreterError> {
+        arithmetic(&Operation { operator, left, right }).map(|obj| obj.kind)
+    }
+
+    #[test]
+    fn overflowing_arithmetic() {
+        use Operator::*;
+
+        let overflow = Err(InterpreterError::IntegerOverflow);
+
+        assert_eq!(operate(Add, Value::Int(i64::MIN), Value::Int(-1)), overflow);
+        assert_eq!(operate(Sub, Value::Int(i64::MIN), Value::Int(-1)), Ok(ObjKind::Int(i64::MIN + 1)));
+        assert_eq!(operate(Mul, Value::Int(i64::MIN), Value::Int(-1)), overflow);
+        assert_eq!(operate(Div, Value::Int(i64::MIN), Value::Int(-1)), overflow);
+
+        // The result has the type of the left operand, so unsigned results can't be negative.
+        assert_eq!(operate(Add, Value::UInt(0), Value::Int(i64::MIN)), overflow);
+        assert_eq!(operate(Sub, Value::UInt(0), Value::Int(i64::MIN)), Ok(ObjKind::UInt(1 << 63)));
+        assert_eq!(operate(Mul, Value::UInt(0), Value::Int(i64::MIN)), Ok(ObjKind::UInt(0)));
+        assert_eq!(operate(Div, Value::UInt(0), Value::Int(i64::MIN)), Ok(ObjKind::UInt(0)));
+
+        assert_eq!(operate(Add, Value::Int(1), Value::UInt(0)), Ok(ObjKind::Int(1)));
+        assert_eq!(operate(Sub, Value::Int(1), Value::UInt(0)), Ok(ObjKind::Int(1)));
+        assert_eq!(operate(Mul, Value::Int(1), Value::UInt(0)), Ok(ObjKind::Int(0)));
+        assert_eq!(operate(Div, Value::Int(1), Value::UInt(0)), Err(InterpreterError::DivisionByZero));
+    }
+
+    fn invalid(expected: &str, lexeme: &str) -> InterpreterError {
+        InterpreterError::InvalidLiteral { expected: expected.to_string(), lexeme: lexeme.to_string() }
+    }
+
+    #[test]
+    fn malformed_literals() {
+        let results = evaluate(&Program {
+            nodes: vec![
+                Node::Expression(Expr::Literal(LiteralKind::Int, String::from("99999999999999999999"))),
+                Node::Expression(Expr::Literal(LiteralKind::Boolean, String::from("maybe"))),
+                Node::Expression(Expr::List(Some(Box::new(Expr::Literal(LiteralKind::Float, String::from("1.2.3")))))),
+            ],
+        });
+
+        let errors: Vec<InterpreterError> = results
+            .into_iter()
+            .map(|result| result.unwrap_err().inner().clone())
+            .collect();
+
+        assert_eq!(errors, vec![
+            invalid("Int", "99999999999999999999"),
+            invalid("Boolean", "maybe"),
+            invalid("Float", "1.2.3"),
+        ]);
+
+        assert_eq!(
+            operate(Operator::Mul, Value::FloatLexeme(String::from("not a number")), Value::Int(2)),
+            Err(invalid("Float", "not a number")),
+        );
+    }
+
+    #[test]
+    fn deeply_nested_code_is_rejected() {
+        use Wrapper::*;
+
+        let too_deep = |levels: usize| Program {
+            nodes: [List, Prefix, Block, Var, Return, Match]
+                .into_iter()
+                .map(|wrapper| Node::Expression(Expr::Nested(wrapper, levels as u16)))
+                .chain([Node::Expression(Expr::Pattern(Pat::Nested(levels as u16)))])
+                .collect(),
+        };
+
+        let rejected = |result: Result<Obj, InterpreterError>| {
+            matches!(result, Err(error) if *error.inner() == InterpreterError::TooDeeplyNested)
+        };
+
+        assert!(evaluate(&too_deep(16)).into_iter().all(|result| !rejected(result)));
+        assert!(evaluate(&too_deep(MAX_NESTING)).into_iter().all(rejected));
+        assert!(evaluate(&too_deep(MAX_GENERATED_NESTING - 1)).into_iter().all(rejected));
+    }
+
+    /// `def f(x) f(x)`, then `f(1)`.
+    fn endless_recursion() -> Program {
+        let call = Expr::Call(Name(2), Some(Pat::Variable(Some(Name(3)), None)));
+
+        Program {
+            nodes: vec![
+                Node::Expression(Expr::Method(Name(2), Some(Pat::Variable(Some(Name(3)), None)), Box::new(call))),
+                Node::Expression(Expr::Call(Name(2), Some(Pat::Value(Box::new(Expr::Int(1)))))),
+            ],
+        }
+    }
+
+    fn too_much_recursion(results: Vec<Result<Obj, InterpreterError>>) -> bool {
+        matches!(results.last(), Some(Err(error)) if *error.inner() == InterpreterError::TooMuchRecursion)
+    }
+
+    #[test]
+    fn endless_recursion_stops_at_the_fuzzing_limit() {
+        assert!(too_much_recursion(evaluate(&endless_recursion())));
+    }
+
+    #[test]
+    fn endless_recursion_stops_at_the_default_limit() {
+        // Test threads get a 2 MiB stack, while the main thread of a program usually gets 8 MiB.
+        // The default recursion limit has to be safe on the main thread, so run there with its size.
+        let thread = std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| {
+                let mut interpreter = Interpreter::with_options(InterpreterOptions {
+                    prelude: false,
+                    ..InterpreterOptions::default()
+                });
+
+                assert_eq!(interpreter.options.recursion_limit, 1000);
+
+                too_much_recursion(evaluate_with(&mut interpreter, &endless_recursion()))
+            })
+            .unwrap();
+
+        assert!(thread.join().unwrap());
+    }
+}
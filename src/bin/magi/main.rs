@@ -0,0 +1,79 @@
+mod repl;
+mod report;
+mod run;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use self::run::RunOptions;
+
+const USAGE: &str = "\
+usage: magi                     start an interactive prompt
+       magi run [OPTIONS] FILE [ARGS...]
+
+options:
+       --recursion-limit N      give up on expressions nested deeper than N
+       --no-prelude             don't import the prelude module";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        None => {
+            repl::run();
+            ExitCode::SUCCESS
+        },
+
+        Some("run") => match self::parse_run_options(&args[1..]) {
+            Ok(options) => run::run(options),
+
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                ExitCode::from(2)
+            },
+        },
+
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        },
+
+        Some(command) => {
+            eprintln!("error: unknown command `{}`\n\n{}", command, USAGE);
+            ExitCode::from(2)
+        },
+    }
+}
+
+/// Read the options of `magi run`. Everything after the file name belongs to the script.
+fn parse_run_options(args: &[String]) -> Result<RunOptions, String> {
+    let mut recursion_limit = None;
+    let mut prelude = true;
+    let mut args = args.iter();
+
+    let path = loop {
+        match args.next().map(String::as_str) {
+            Some("--recursion-limit") => {
+                let limit = args.next().ok_or("--recursion-limit needs a value")?;
+
+                recursion_limit = Some(
+                    limit.parse::<usize>().map_err(|_| format!("invalid recursion limit `{}`", limit))?
+                );
+            },
+
+            Some("--no-prelude") => prelude = false,
+
+            Some(option) if option.starts_with("--") => return Err(format!("unknown option `{}`", option)),
+
+            Some(path) => break PathBuf::from(path),
+            None       => return Err(String::from("missing the file to run")),
+        }
+    };
+
+    Ok(RunOptions {
+        path,
+        args: args.cloned().collect(),
+        recursion_limit,
+        prelude,
+    })
+}
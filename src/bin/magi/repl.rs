@@ -0,0 +1,364 @@
+//! An interactive prompt which evaluates Mag code line by line.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+
+use magc::type_system::Typed;
+
+use magi::interpreter::loader::{self, FileLoader};
+use magi::interpreter::Interpreter;
+
+use crate::{report, run};
+use magi::types::{ObjKind, VariablePattern};
+
+const PROMPT:        &str       = "mag> ";
+const HISTORY_FILE:  &str       = ".magi_history";
+/// The name used for code typed at the prompt in error messages.
+const REPL_PATH:     &str       = "<repl>";
+const META_COMMANDS: [&str; 5]  = [":env", ":methods", ":reset", ":help", ":quit"];
+
+/// Read lines of code until the user quits, keeping all definitions between them.
+pub fn run() {
+    let mut editor = Editor::<MagHelper>::new();
+    editor.set_helper(Some(MagHelper::default()));
+
+    let history = self::history_path();
+
+    if let Some(path) = &history {
+        // There is no history yet the first time the prompt is used.
+        let _ = editor.load_history(path);
+    }
+
+    let mut interpreter = self::new_interpreter();
+
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.names = self::names(&interpreter);
+        }
+
+        let input = match editor.readline(PROMPT) {
+            Ok(input) => input,
+
+            // Ctrl-C discards the current input, but keeps the session.
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof)         => break,
+
+            Err(e) => {
+                eprintln!("error: {}", e);
+                break
+            },
+        };
+
+        let input = input.trim();
+
+        if input.is_empty() {
+            continue
+        }
+
+        editor.add_history_entry(input);
+
+        if input.starts_with(':') {
+            match self::run_command(&mut interpreter, input) {
+                Command::Continue => continue,
+                Command::Quit     => break,
+            }
+        }
+
+        self::evaluate(&mut interpreter, input);
+    }
+
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("error: could not save history: {}", e);
+        }
+    }
+}
+
+fn new_interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new();
+
+    // The prelude only comes from `MAG_PATH`, so a `prelude.mag` in the working directory is left alone.
+    interpreter.loader = Box::new(FileLoader::new(run::mag_path()));
+
+    if let Err(e) = interpreter.load_prelude() {
+        eprintln!("error: could not load the prelude: {}", e);
+    }
+
+    let mut search_paths = vec![PathBuf::from(".")];
+    search_paths.extend(run::mag_path());
+
+    interpreter.loader = Box::new(FileLoader::new(search_paths));
+
+    interpreter
+}
+
+/// Evaluate some code and print the value of each expression.
+fn evaluate(interpreter: &mut Interpreter, input: &str) {
+    let objs = match loader::parse(input, REPL_PATH) {
+        Ok(objs) => objs,
+
+        Err(e) => {
+            report::print_error(interpreter, &e, REPL_PATH, input);
+            return
+        },
+    };
+
+    for obj in objs {
+        let result = interpreter.evaluate(Box::new(obj), None);
+        report::print_warnings(interpreter);
+
+        match result {
+            Ok(value) => println!("{}", value),
+
+            // Everything evaluated before the error is kept, so the session goes on from there.
+            Err(e) => {
+                report::print_error(interpreter, &e, REPL_PATH, input);
+                return
+            },
+        }
+    }
+}
+
+enum Command {
+    Continue,
+    Quit,
+}
+
+fn run_command(interpreter: &mut Interpreter, input: &str) -> Command {
+    let mut words = input.split_whitespace();
+
+    match (words.next(), words.next()) {
+        (Some(":env"), None) => {
+            let mut entries: Vec<_> = interpreter.environment.entries.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+
+            for (variable_pattern, obj) in entries {
+                let name = variable_pattern.name.clone().unwrap_or_else(|| String::from("_"));
+                let type_id = obj.get_type().unwrap_or_else(|| String::from("?"));
+
+                println!("{}: {}", name, type_id);
+            }
+        },
+
+        (Some(":methods"), Some(name)) => {
+            match interpreter.get_variable(VariablePattern::from_name(name.to_string()), None) {
+                Ok(obj) => match obj.kind {
+                    ObjKind::Multimethod(multimethod) => {
+                        for receiver in multimethod.receivers {
+                            let guard = match &receiver.guard {
+                                Some(guard) => format!(" if {}", guard),
+                                None        => String::new(),
+                            };
+
+                            match receiver.signature {
+                                Some(signature) => println!("{}({}){}", name, signature, guard),
+                                None            => println!("{}(){}", name, guard),
+                            }
+                        }
+                    },
+
+                    _ => eprintln!("error: `{}` is not a multimethod", name),
+                },
+
+                Err(e) => eprintln!("error: {}", e),
+            }
+        },
+
+        (Some(":reset"), None) => {
+            *interpreter = self::new_interpreter();
+            println!("All definitions have been removed.");
+        },
+
+        (Some(":help"), None) => {
+            println!(":env            list all variables and their types");
+            println!(":methods NAME   list the receivers of a multimethod");
+            println!(":reset          remove all definitions");
+            println!(":quit           leave the prompt");
+        },
+
+        (Some(":quit"), None) => return Command::Quit,
+
+        _ => eprintln!("error: unknown command `{}`, try :help", input),
+    }
+
+    Command::Continue
+}
+
+/// Collect the names which are visible at the top level, including the exports of imported modules.
+fn names(interpreter: &Interpreter) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+
+    for (variable_pattern, obj) in &interpreter.environment.entries {
+        let name = match &variable_pattern.name {
+            Some(name) => name,
+            None       => continue,
+        };
+
+        if let ObjKind::Module(module) = &obj.kind {
+            names.extend(module.exports.iter().map(|export| format!("{}.{}", name, export)));
+        }
+
+        names.insert(name.clone());
+    }
+
+    names
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Completes names and asks for more lines while brackets or strings are still open.
+#[derive(Default)]
+struct MagHelper {
+    names: BTreeSet<String>,
+}
+
+impl Completer for MagHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl MagHelper {
+    /// Return where the word before the cursor starts and the names it could be completed to.
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let start = self::word_start(line, pos);
+        let prefix = &line[start..pos];
+
+        let candidates: Box<dyn Iterator<Item = &str>> = if prefix.starts_with(':') {
+            Box::new(META_COMMANDS.iter().copied())
+        } else {
+            Box::new(self.names.iter().map(String::as_str))
+        };
+
+        let pairs = candidates
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair {
+                display:     name.to_string(),
+                replacement: name.to_string(),
+            })
+            .collect();
+
+        (start, pairs)
+    }
+}
+
+/// Return the byte index at which the word ending at the given position starts.
+fn word_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '.' || *c == ':'))
+        .map_or(0, |(index, c)| index + c.len_utf8())
+}
+
+impl Validator for MagHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if self::is_complete(ctx.input()) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+impl Hinter for MagHelper {
+    type Hint = String;
+}
+
+impl Highlighter for MagHelper {}
+
+impl Helper for MagHelper {}
+
+/// Return whether all brackets and strings in the given code are closed.
+fn is_complete(input: &str) -> bool {
+    let mut depth: isize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in input.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\'         => escaped = true,
+                '"'          => in_string = false,
+                _            => (),
+            }
+
+            continue
+        }
+
+        match c {
+            '"'             => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _               => (),
+        }
+    }
+
+    // Too many closing brackets won't get better with more lines, so let the parser report them.
+    !in_string && depth <= 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper(names: &[&str]) -> MagHelper {
+        MagHelper {
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn replacements(helper: &MagHelper, line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = helper.candidates(line, line.len());
+
+        (start, pairs.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    #[test]
+    fn words_start_after_the_last_delimiter() {
+        assert_eq!(self::word_start("fib(ma", 6), 4);
+        assert_eq!(self::word_start("config.an", 9), 0);
+        assert_eq!(self::word_start("", 0), 0);
+    }
+
+    #[test]
+    fn multibyte_delimiters_are_skipped_as_a_whole() {
+        let helper = helper(&["x", "xs"]);
+
+        assert_eq!(replacements(&helper, "€ x"), (4, vec![String::from("x"), String::from("xs")]));
+        assert_eq!(replacements(&helper, "€x"), (3, vec![String::from("x"), String::from("xs")]));
+    }
+
+    #[test]
+    fn meta_commands_are_completed_after_a_colon() {
+        assert_eq!(replacements(&helper(&[]), ":re"), (0, vec![String::from(":reset")]));
+    }
+
+    #[test]
+    fn input_is_complete_once_brackets_and_strings_are_closed() {
+        assert!(self::is_complete("fib(10)"));
+        assert!(self::is_complete("[1, (2, 3)]"));
+        assert!(self::is_complete("\"(\""));
+        assert!(self::is_complete("\"\\\"\""));
+
+        assert!(!self::is_complete("fib("));
+        assert!(!self::is_complete("[1, (2"));
+        assert!(!self::is_complete("\"open"));
+        assert!(!self::is_complete("\"escaped \\\""));
+
+        // Extra closing brackets are left for the parser to report.
+        assert!(self::is_complete("fib())"));
+    }
+}
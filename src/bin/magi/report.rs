@@ -0,0 +1,100 @@
+//! Printing errors for the user.
+
+use magi::interpreter::diagnostic::Diagnostic;
+use magi::interpreter::trace::Frame;
+use magi::interpreter::{Interpreter, InterpreterError};
+
+/// Print an error along with the line of code it came from and the calls leading up to it.
+///
+/// The path and source are the ones of the main program. Errors raised inside of
+/// an imported module are shown with that module's source code instead.
+pub fn print_error(interpreter: &Interpreter, error: &InterpreterError, path: &str, source: &str) {
+    match self::module_source(interpreter, error.module(), path, source) {
+        Some((path, source)) => eprint!("{}", Diagnostic::new(error, &path, &source)),
+        None                 => eprintln!("error: {}", error),
+    }
+
+    if let Some(trace) = error.trace() {
+        eprintln!("call stack, innermost call first:");
+
+        for frame in trace.iter().rev() {
+            eprintln!("    in {}{}", frame, self::call_site(interpreter, frame, path, source));
+        }
+    }
+}
+
+/// Print the warnings collected since the last call and forget about them.
+pub fn print_warnings(interpreter: &mut Interpreter) {
+    for warning in interpreter.warnings.drain(..) {
+        eprintln!("warning: {}", warning);
+    }
+}
+
+/// Describe where a call was made, like ` called at main.mag:4:1`.
+///
+/// Modules added with `Interpreter::add_module` have no source code to count lines in,
+/// so calls made from them are described by the position in the module instead.
+fn call_site(interpreter: &Interpreter, frame: &Frame, path: &str, source: &str) -> String {
+    let span = match frame.call_site {
+        Some(span) => span,
+        None       => return String::new(),
+    };
+
+    match self::module_source(interpreter, frame.module.as_deref(), path, source) {
+        Some((path, source)) => {
+            let (line, column) = span.line_and_column(&source);
+
+            format!(" called at {}:{}:{}", path, line, column)
+        },
+
+        None => match frame.module.as_deref() {
+            Some(module) if interpreter.module_sources.contains_key(module) => {
+                format!(" called in {} at position {}", module, span.start)
+            },
+
+            _ => String::new(),
+        },
+    }
+}
+
+/// Return the path and source code of a module, or of the main program if there is no module.
+fn module_source(interpreter: &Interpreter, module: Option<&str>, path: &str, source: &str) -> Option<(String, String)> {
+    match module {
+        None         => Some((path.to_string(), source.to_string())),
+        Some(module) => interpreter.loader.read(module).ok().map(|source| (module.to_string(), source)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magi::interpreter::diagnostic::Span;
+
+    use super::*;
+
+    fn frame(module: Option<&str>) -> Frame {
+        Frame::new(String::from("fib"), None, Some(Span::new(4, 10)), module.map(String::from))
+    }
+
+    #[test]
+    fn call_sites_in_the_main_program_have_a_line_and_column() {
+        let interpreter = Interpreter::new();
+
+        assert_eq!(
+            self::call_site(&interpreter, &frame(None), "main.mag", "one\nfib(1)"),
+            " called at main.mag:2:1",
+        );
+    }
+
+    #[test]
+    fn call_sites_in_added_modules_fall_back_to_the_position() {
+        let mut interpreter = Interpreter::new();
+        interpreter.add_module("config", vec![]);
+
+        assert_eq!(
+            self::call_site(&interpreter, &frame(Some("config")), "main.mag", ""),
+            " called in config at position 4",
+        );
+
+        assert_eq!(self::call_site(&interpreter, &frame(Some("missing")), "main.mag", ""), "");
+    }
+}
@@ -0,0 +1,97 @@
+//! Running a Mag script from a file.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use magi::interpreter::loader::{self, FileLoader};
+use magi::interpreter::{Interpreter, InterpreterError, InterpreterOptions};
+use magi::types::{Obj, ObjKind, VariablePattern};
+
+use crate::report;
+
+/// The environment variable holding extra directories to search for modules.
+const MAG_PATH: &str = "MAG_PATH";
+
+/// Settings for running a script, taken from the command line.
+pub struct RunOptions {
+    pub path:            PathBuf,
+    /// Arguments passed on to the script, which it sees as the list `args`.
+    pub args:            Vec<String>,
+    pub recursion_limit: Option<usize>,
+    pub prelude:         bool,
+}
+
+/// Evaluate all top-level expressions of a script in order, stopping at the first error.
+pub fn run(options: RunOptions) -> ExitCode {
+    let path = options.path.display().to_string();
+
+    let source = match std::fs::read_to_string(&options.path) {
+        Ok(source) => source,
+
+        Err(e) => {
+            eprintln!("error: could not read `{}`: {}", path, e);
+            return ExitCode::FAILURE
+        },
+    };
+
+    let defaults = InterpreterOptions::default();
+
+    let interpreter_options = InterpreterOptions {
+        prelude:         options.prelude,
+        recursion_limit: options.recursion_limit.unwrap_or(defaults.recursion_limit),
+        ..defaults
+    };
+
+    let mut interpreter = Interpreter::with_options(interpreter_options);
+    interpreter.loader = Box::new(FileLoader::new(self::search_paths(&options.path)));
+
+    let result = self::evaluate(&mut interpreter, options.args, &source, &path);
+    report::print_warnings(&mut interpreter);
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+
+        Err(e) => {
+            report::print_error(&interpreter, &e, &path, &source);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn evaluate(interpreter: &mut Interpreter, args: Vec<String>, source: &str, path: &str) -> Result<(), InterpreterError> {
+    let args = args
+        .into_iter()
+        .map(|arg| Obj::new(ObjKind::String(arg)))
+        .collect();
+
+    // Defined first, so a prelude exporting `args` of its own doesn't hide the script's arguments.
+    interpreter.define_variable(VariablePattern::from_name(String::from("args")), Obj::list(args))?;
+    interpreter.load_prelude()?;
+
+    for obj in loader::parse(source, path)? {
+        interpreter.evaluate(Box::new(obj), None)?;
+    }
+
+    Ok(())
+}
+
+/// Search for modules next to the script first and then in the directories listed in `MAG_PATH`.
+fn search_paths(script: &Path) -> Vec<PathBuf> {
+    let directory = match script.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _                                              => PathBuf::from("."),
+    };
+
+    let mut search_paths = vec![directory];
+    search_paths.extend(self::mag_path());
+
+    search_paths
+}
+
+/// Return the directories listed in `MAG_PATH`, if it is set.
+pub fn mag_path() -> Vec<PathBuf> {
+    match std::env::var_os(MAG_PATH) {
+        Some(mag_path) => std::env::split_paths(&mag_path).collect(),
+        None           => vec![],
+    }
+}
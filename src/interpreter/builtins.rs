@@ -0,0 +1,115 @@
+//! Constructors for values which have no literal syntax of their own.
+//!
+//! A call like `uint(5)` or `error("NotFound", "no such user")` is only handled here
+//! if no variable with that name is defined, so programs can still define their own
+//! multimethods with these names.
+
+use magc::type_system::Typed;
+
+use crate::types::{ErrorValue, Obj, ObjKind, PairPattern, Pattern, TuplePattern, ValuePattern};
+
+use super::InterpreterError;
+
+/// Return true if there is a builtin constructor with the given name.
+pub fn is_constructor(name: &str) -> bool {
+    matches!(name, "uint" | "error")
+}
+
+/// Create a value with the builtin constructor of the given name from the evaluated arguments of a call.
+pub fn construct(name: &str, arguments: Option<Pattern>) -> Result<Obj, InterpreterError> {
+    match name {
+        "uint"  => self::uint(arguments),
+        "error" => self::error(arguments),
+
+        _ => Err(InterpreterError::NoMatchingMultimethod),
+    }
+}
+
+/// `uint(n)` converts an integer which is not negative into an unsigned integer.
+fn uint(arguments: Option<Pattern>) -> Result<Obj, InterpreterError> {
+    let value = self::single_argument(arguments, "Int")?;
+
+    match value.kind {
+        ObjKind::UInt(_) => Ok(value),
+
+        ObjKind::Int(int) => match u64::try_from(int) {
+            Ok(uint) => Ok(Obj::new(ObjKind::UInt(uint))),
+            Err(_)   => Err(InterpreterError::IntegerOverflow),
+        },
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("Int"),
+            found:    value.get_type(),
+        }),
+    }
+}
+
+/// `error(kind, message)` or `error(kind, message, payload)` creates an error value, which can be thrown.
+///
+/// The payload is `nothing` unless it is given.
+fn error(arguments: Option<Pattern>) -> Result<Obj, InterpreterError> {
+    let mut values = self::positional_arguments(arguments).into_iter();
+
+    let (kind, message, payload) = match (values.next(), values.next(), values.next(), values.next()) {
+        (Some(kind), Some(message), payload, None) => (kind, message, payload),
+
+        _ => return Err(InterpreterError::NoMatchingReceiver),
+    };
+
+    let payload = payload.unwrap_or_else(|| Obj::new(ObjKind::Nothing));
+
+    Ok(Obj::new(ObjKind::Error(ErrorValue::new(
+        self::expect_string(kind)?,
+        self::expect_string(message)?,
+        Box::new(payload),
+    ))))
+}
+
+/// Return the values of positional arguments, which are given as a tree of pair patterns.
+///
+/// Structured arguments, like a tuple, are kept as a pattern value.
+fn positional_arguments(arguments: Option<Pattern>) -> Vec<Obj> {
+    let mut values = vec![];
+
+    match arguments {
+        Some(Pattern::Tuple(TuplePattern { child })) => self::collect_arguments(*child, &mut values),
+        Some(arguments)                              => self::collect_arguments(arguments, &mut values),
+        None                                         => (),
+    }
+
+    values
+}
+
+fn collect_arguments(pattern: Pattern, values: &mut Vec<Obj>) {
+    match pattern {
+        Pattern::Pair(PairPattern { left, right }) => {
+            self::collect_arguments(*left, values);
+            self::collect_arguments(*right, values);
+        },
+
+        Pattern::Value(ValuePattern { obj }) => values.push(*obj),
+        pattern                              => values.push(Obj::new(ObjKind::Pattern(pattern))),
+    }
+}
+
+fn expect_string(obj: Obj) -> Result<String, InterpreterError> {
+    match obj.kind {
+        ObjKind::String(string) => Ok(string),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("String"),
+            found:    obj.get_type(),
+        }),
+    }
+}
+
+fn single_argument(arguments: Option<Pattern>, expected: &str) -> Result<Obj, InterpreterError> {
+    match arguments {
+        Some(Pattern::Value(ValuePattern { obj })) => Ok(*obj),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: expected.to_string(),
+            found:    arguments.map(|pattern| Obj::new(ObjKind::Pattern(pattern))).and_then(|obj| obj.get_type()),
+        }),
+    }
+}
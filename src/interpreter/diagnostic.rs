@@ -0,0 +1,336 @@
+//! Human readable error messages, pointing at the code which caused them.
+
+use std::fmt::{self, Display, Formatter};
+
+use magc::types::Expression;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::types::obj::MAX_NESTING;
+
+use super::InterpreterError;
+use super::trace::Frame;
+
+/// A range of positions in the source code of a program, counted in graphemes like `magc` does.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end:   usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+        }
+    }
+}
+
+impl From<&Expression> for Span {
+    fn from(expression: &Expression) -> Self {
+        Self::new(expression.start_pos, expression.end_pos)
+    }
+}
+
+impl InterpreterError {
+    /// Attach the location of the code which raised this error, unless it already has one.
+    ///
+    /// The module is the path of the module the code belongs to, or `None` for the main program.
+    pub fn at(self, span: Span, module: Option<String>) -> Self {
+        if self.span().is_some() {
+            return self
+        }
+
+        InterpreterError::Located {
+            error: Box::new(self),
+            span,
+            module,
+        }
+    }
+
+    /// Attach the call stack at the point where this error escaped from a receiver, unless it already has one.
+    pub fn traced(self, call_stack: &[Frame]) -> Self {
+        if self.trace().is_some() {
+            return self
+        }
+
+        InterpreterError::Traced {
+            error: Box::new(self),
+            trace: call_stack.to_vec(),
+        }
+    }
+
+    /// Return the location of the code which raised this error, if it is known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            InterpreterError::Located { span, .. } => Some(*span),
+            InterpreterError::Traced { error, .. } => error.span(),
+
+            _ => None,
+        }
+    }
+
+    /// Return the path of the module whose code raised this error, or `None` for the main program.
+    pub fn module(&self) -> Option<&str> {
+        match self {
+            InterpreterError::Located { module, .. } => module.as_deref(),
+            InterpreterError::Traced { error, .. }   => error.module(),
+
+            _ => None,
+        }
+    }
+
+    /// Return the calls which were being evaluated when this error was raised, with the innermost call last.
+    pub fn trace(&self) -> Option<&[Frame]> {
+        match self {
+            InterpreterError::Traced { trace, .. }  => Some(trace),
+            InterpreterError::Located { error, .. } => error.trace(),
+
+            _ => None,
+        }
+    }
+
+    /// Return the error without any location or call stack attached to it.
+    pub fn inner(&self) -> &InterpreterError {
+        match self {
+            InterpreterError::Located { error, .. } => error.inner(),
+            InterpreterError::Traced { error, .. }  => error.inner(),
+
+            error => error,
+        }
+    }
+
+    /// Return additional lines of explanation shown below the message.
+    pub fn notes(&self) -> Vec<String> {
+        match self.inner() {
+            InterpreterError::AssignToImmutable { .. } => vec![
+                String::from("variables are immutable unless they are defined with `let mut`"),
+            ],
+
+            InterpreterError::InconsistentBindings { expected, found } => vec![
+                format!("the first alternative binds: {}", expected.join(", ")),
+                format!("this alternative binds: {}", found.join(", ")),
+            ],
+
+            InterpreterError::ImportCycle { chain } => vec![
+                format!("the modules import each other like this: {}", chain.join(" -> ")),
+            ],
+
+            InterpreterError::TooMuchRecursion | InterpreterError::StepLimitExceeded => vec![
+                String::from("the limit can be raised in the interpreter options"),
+            ],
+
+            InterpreterError::Thrown { error } => vec![
+                format!("the error carries the payload {}", error.payload),
+            ],
+
+            _ => vec![],
+        }
+    }
+}
+
+impl Display for InterpreterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpreterError::Located { error, .. } => write!(f, "{}", error),
+            InterpreterError::Traced { error, .. }  => write!(f, "{}", error),
+
+            InterpreterError::Unimplemented => write!(f, "this feature is not implemented yet"),
+
+            InterpreterError::UnexpectedType { expected, found } => write!(
+                f,
+                "expected a value of type {}, found {}",
+                expected,
+                found.as_deref().unwrap_or("a value without a type"),
+            ),
+
+            InterpreterError::MethodAlreadyExists    => write!(f, "a receiver with this signature already exists"),
+            InterpreterError::SignatureAlreadyExists => write!(f, "a receiver with this signature and guard already exists"),
+
+            InterpreterError::VariableAlreadyExists { variable_pattern } => {
+                write!(f, "`{}` is already defined in this scope", variable_pattern)
+            },
+
+            InterpreterError::NoMatchingReceiver    => write!(f, "no receiver matches the given arguments"),
+            InterpreterError::NoMatchingMultimethod => write!(f, "there is no multimethod with this name"),
+
+            InterpreterError::NoMatchingVariable { variable_pattern } => write!(f, "`{}` is not defined", variable_pattern),
+
+            InterpreterError::AssignToUndefined { variable_pattern } => {
+                write!(f, "cannot assign to `{}` because it is not defined", variable_pattern)
+            },
+
+            InterpreterError::AssignToImmutable { variable_pattern } => {
+                write!(f, "cannot assign to the immutable variable `{}`", variable_pattern)
+            },
+
+            InterpreterError::NoMatchingVisitor        => write!(f, "this kind of expression can't be evaluated"),
+            InterpreterError::NoMatchingArm { value }  => write!(f, "no arm matches the value {}", value),
+            InterpreterError::NoMatch                  => write!(f, "the pattern does not match"),
+            InterpreterError::DuplicateField { name }  => write!(f, "the field `{}` occurs more than once", name),
+            InterpreterError::UnknownField { name }    => write!(f, "there is no field named `{}`", name),
+            InterpreterError::MissingField { name }    => write!(f, "the field `{}` is missing", name),
+            InterpreterError::PositionalAfterField     => write!(f, "positional items have to come before fields"),
+            InterpreterError::DuplicateBinding { name } => write!(f, "the variable `{}` is bound more than once", name),
+            InterpreterError::InconsistentBindings { .. } => write!(f, "all alternatives have to bind the same variables"),
+
+            InterpreterError::NoMatchingModule { name } => write!(f, "there is no module named `{}`", name),
+
+            InterpreterError::ModuleLoadFailed { path, message } => write!(f, "could not read `{}`: {}", path, message),
+            InterpreterError::ParseFailed { path, message }      => write!(f, "could not parse `{}`: {}", path, message),
+
+            InterpreterError::ImportCycle { chain } => {
+                write!(f, "the module `{}` imports itself", chain.first().map(String::as_str).unwrap_or_default())
+            },
+
+            InterpreterError::NoMatchingExport { module, name } => {
+                write!(f, "the module `{}` does not export `{}`", module, name)
+            },
+
+            InterpreterError::ConflictingImport { name } => {
+                write!(f, "the imported multimethod `{}` has a receiver which conflicts with an existing one", name)
+            },
+
+            InterpreterError::InvalidLiteral { expected, lexeme } => write!(f, "`{}` is not a valid {} literal", lexeme, expected),
+
+            InterpreterError::DivisionByZero   => write!(f, "division by zero"),
+            InterpreterError::IntegerOverflow  => write!(f, "the result of this integer operation is out of range"),
+            InterpreterError::TooMuchRecursion => write!(f, "too much recursion"),
+            InterpreterError::TooDeeplyNested  => write!(f, "this code is nested more than {} levels deep", MAX_NESTING),
+
+            InterpreterError::StepLimitExceeded => write!(f, "the evaluation took too many steps"),
+
+            InterpreterError::Thrown { error } => write!(f, "uncaught {}: {}", error.kind, error.message),
+        }
+    }
+}
+
+impl std::error::Error for InterpreterError {}
+
+/// An error along with the code it came from, which is displayed like a compiler error.
+///
+/// ```text
+/// error: `x` is not defined
+///  --> main.mag:3:5
+///   |
+/// 3 | add(x, 1)
+///   |     ^
+/// ```
+///
+/// The source code has to be the one of the module the error came from, as returned by
+/// [`InterpreterError::module`].
+pub struct Diagnostic<'a> {
+    pub error:  &'a InterpreterError,
+    /// The name of the file the source code was read from.
+    pub path:   &'a str,
+    pub source: &'a str,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(error: &'a InterpreterError, path: &'a str, source: &'a str) -> Self {
+        Self {
+            error,
+            path,
+            source,
+        }
+    }
+}
+
+impl<'a> Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: {}", self.error)?;
+
+        let gutter = match self.error.span() {
+            Some(span) => {
+                let location = Location::find(self.source, span);
+                let gutter = " ".repeat(location.line.to_string().len());
+
+                writeln!(f, "{}--> {}:{}:{}", gutter, self.path, location.line, location.column)?;
+                writeln!(f, "{} |", gutter)?;
+                writeln!(f, "{} | {}", location.line, location.text)?;
+                writeln!(f, "{} | {}{}", gutter, " ".repeat(location.column - 1), "^".repeat(location.width))?;
+
+                gutter
+            },
+
+            None => {
+                writeln!(f, " --> {}", self.path)?;
+
+                String::new()
+            },
+        };
+
+        for note in self.error.notes() {
+            writeln!(f, "{} = note: {}", gutter, note)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Span {
+    /// Return the one-based line and column in the given source code where this span starts.
+    pub fn line_and_column(&self, source: &str) -> (usize, usize) {
+        let location = Location::find(source, *self);
+
+        (location.line, location.column)
+    }
+}
+
+/// The line a span starts on, with one-based line and column numbers counted in characters.
+struct Location<'a> {
+    line:   usize,
+    column: usize,
+    /// The number of characters of the span on its first line, but at least one.
+    width:  usize,
+    text:   &'a str,
+}
+
+impl<'a> Location<'a> {
+    fn find(source: &'a str, span: Span) -> Self {
+        let start = self::byte_offset(source, span.start);
+        let end   = self::byte_offset(source, span.end.max(span.start));
+
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end   = source[start..].find('\n').map_or(source.len(), |index| start + index);
+
+        let text = source[line_start..line_end].trim_end_matches('\r');
+
+        Self {
+            line:   source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            width:  source[start..end.min(line_end)].chars().count().max(1),
+            text,
+        }
+    }
+}
+
+/// Convert a position counted in graphemes into a byte offset, staying inside of the source.
+fn byte_offset(source: &str, position: usize) -> usize {
+    source.grapheme_indices(true).nth(position).map_or(source.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VariablePattern;
+
+    #[test]
+    fn diagnostics_underline_the_span() {
+        let source = "let y = 1\nadd(x, 1)\n";
+        let error = InterpreterError::NoMatchingVariable {
+            variable_pattern: VariablePattern::from_name(String::from("x")),
+        }.at(Span::new(14, 15), None);
+
+        assert_eq!(
+            Diagnostic::new(&error, "main.mag", source).to_string(),
+            concat!(
+                "error: `x` is not defined\n",
+                " --> main.mag:2:5\n",
+                "  |\n",
+                "2 | add(x, 1)\n",
+                "  |     ^\n",
+            ),
+        );
+    }
+}
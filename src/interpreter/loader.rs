@@ -0,0 +1,284 @@
+//! Finding the source code of modules and turning it into objects the interpreter can evaluate.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use magc::lexer::Lexer;
+use magc::parser::Parser;
+use magc::types::{Expression, Keyword, ParserError, Token, TokenKind};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::types::{Obj, Source};
+use crate::types::obj::nesting;
+
+use super::InterpreterError;
+
+/// The file extension of Mag source files.
+pub const EXTENSION: &str = "mag";
+
+/// The word introducing the guard clause of a method definition, like `def fact(n Int) where n > 0`.
+pub const GUARD_KEYWORD: &str = "where";
+
+/// Resolves module names to source code.
+///
+/// The interpreter uses the path returned by `resolve` to identify a module, so two
+/// names resolving to the same path share a single evaluated module.
+pub trait ModuleLoader {
+    /// Find the module with the given name and return a path which uniquely identifies it.
+    fn resolve(&self, name: &str) -> Result<String, InterpreterError>;
+
+    /// Return the source code of a module found by `resolve`.
+    fn read(&self, path: &str) -> Result<String, InterpreterError>;
+}
+
+/// Loads modules from files in a list of directories, which are searched in order.
+///
+/// A module name like `net.http` refers to the file `net/http.mag` inside one of the directories.
+#[derive(Debug, Clone)]
+pub struct FileLoader {
+    pub search_paths: Vec<PathBuf>,
+}
+
+impl FileLoader {
+    pub fn new(search_paths: Vec<PathBuf>) -> Self {
+        Self {
+            search_paths,
+        }
+    }
+}
+
+impl Default for FileLoader {
+    fn default() -> Self {
+        Self::new(vec![PathBuf::from(".")])
+    }
+}
+
+impl ModuleLoader for FileLoader {
+    fn resolve(&self, name: &str) -> Result<String, InterpreterError> {
+        let relative: PathBuf = name.split('.').collect::<PathBuf>().with_extension(EXTENSION);
+
+        self.search_paths
+            .iter()
+            .map(|directory| directory.join(&relative))
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| candidate.canonicalize().ok())
+            .map(|path| path.display().to_string())
+            .ok_or_else(|| InterpreterError::NoMatchingModule { name: name.to_string() })
+    }
+
+    fn read(&self, path: &str) -> Result<String, InterpreterError> {
+        std::fs::read_to_string(path).map_err(|e| InterpreterError::ModuleLoadFailed {
+            path:    path.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Serves modules from source code kept in memory, keyed by module name.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    pub sources: HashMap<String, String>,
+}
+
+impl MemoryLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, source: &str) {
+        self.sources.insert(name.to_string(), source.to_string());
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn resolve(&self, name: &str) -> Result<String, InterpreterError> {
+        if self.sources.contains_key(name) {
+            Ok(name.to_string())
+        } else {
+            Err(InterpreterError::NoMatchingModule { name: name.to_string() })
+        }
+    }
+
+    fn read(&self, path: &str) -> Result<String, InterpreterError> {
+        match self.sources.get(path) {
+            Some(source) => Ok(source.clone()),
+            None         => Err(InterpreterError::NoMatchingModule { name: path.to_string() }),
+        }
+    }
+}
+
+/// Parse the source code of a module into a list of top-level expressions.
+///
+/// The path is only used to tell where a syntax error comes from.
+pub fn parse(source: &str, path: &str) -> Result<Vec<Obj>, InterpreterError> {
+    let mut lexer = Lexer::new();
+    lexer.add_text(source.to_string());
+    let (tokens, guards) = self::extract_guards(source, path, lexer.parse())?;
+
+    let mut parser = Parser::new();
+    parser.add_tokens(source.to_string(), tokens);
+
+    let expressions = parser.parse().map_err(|e| self::parse_failed(path, e))?;
+
+    // Guards are not part of the parsed expressions, so their nesting is checked on its own.
+    for guard in guards.values() {
+        nesting::check_expression(guard, 0)?;
+    }
+
+    let source = Source::new(source).with_guards(guards);
+
+    expressions.into_iter().map(|expression| Obj::from_expression(expression, &source)).collect()
+}
+
+/// Take the guard clauses out of the method definitions in a token stream, since `magc` cannot parse them.
+///
+/// A guard follows the closing parenthesis of the signature and extends as far as a single
+/// expression does, so `def fact(n Int) where n > 0 n * fact(n - 1)` has the guard `n > 0`.
+/// The guards are returned keyed by the position of their `def`, which is where the parsed
+/// method expression starts.
+fn extract_guards(source: &str, path: &str, tokens: Vec<Token>) -> Result<(Vec<Token>, HashMap<usize, Expression>), InterpreterError> {
+    let graphemes: Vec<&str> = source.graphemes(true).collect();
+    let mut guards  = HashMap::new();
+    let mut removed = vec![false; tokens.len()];
+
+    for (index, token) in tokens.iter().enumerate() {
+        if removed[index] || token.kind != TokenKind::Keyword(Keyword::Def) {
+            continue;
+        }
+
+        let Some(close) = self::closing_paren(&tokens, index + 2) else {
+            continue;
+        };
+
+        let is_guard = tokens.get(close + 1).is_some_and(|next| {
+            next.kind == TokenKind::Identifier && graphemes.get(next.start_pos..next.end_pos).map(|g| g.concat()).as_deref() == Some(GUARD_KEYWORD)
+        });
+
+        if is_guard {
+            let (guard, length) = self::parse_guard(source, path, &tokens[close + 2..])?;
+
+            removed[close + 1..close + 2 + length].fill(true);
+            guards.insert(token.start_pos, guard);
+        }
+    }
+
+    let tokens = tokens
+        .into_iter()
+        .zip(removed)
+        .filter_map(|(token, removed)| if removed { None } else { Some(token) })
+        .collect();
+
+    Ok((tokens, guards))
+}
+
+/// Return the index of the parenthesis closing the one at the given index, if there is one.
+fn closing_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    if tokens.get(open)?.kind != TokenKind::LeftParen {
+        return None;
+    }
+
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::LeftParen  => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            _                     => (),
+        }
+
+        if depth == 0 {
+            return Some(index);
+        }
+    }
+
+    None
+}
+
+/// Parse the expression at the start of the given tokens and return it with the number of tokens it spans.
+fn parse_guard(source: &str, path: &str, tokens: &[Token]) -> Result<(Expression, usize), InterpreterError> {
+    let mut parser = Parser::new();
+    parser.add_tokens(source.to_string(), tokens.to_vec());
+    let guard = parser.parse_expression(0).map_err(|e| self::parse_failed(path, e))?;
+
+    // The parser does not tell how far it got, so find the shortest run of tokens parsing to the same expression.
+    for length in 1..=tokens.len() {
+        let mut parser = Parser::new();
+        parser.add_tokens(source.to_string(), tokens[..length].to_vec());
+
+        if let Ok(expressions) = parser.parse() {
+            if let [expression] = expressions.as_slice() {
+                if expression.kind == guard.kind {
+                    return Ok((guard, length));
+                }
+            }
+        }
+    }
+
+    Err(self::parse_failed(path, ParserError::UnexpectedEOF))
+}
+
+fn parse_failed(path: &str, error: ParserError) -> InterpreterError {
+    InterpreterError::ParseFailed {
+        path:    path.to_string(),
+        message: format!("{:?}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magc::types::ExpressionKind;
+
+    use super::*;
+
+    #[test]
+    fn memory_loaders_resolve_names_they_have_sources_for() {
+        let mut loader = MemoryLoader::new();
+        loader.insert("net.http", "source");
+
+        assert_eq!(loader.resolve("net.http"), Ok(String::from("net.http")));
+        assert_eq!(loader.read("net.http"), Ok(String::from("source")));
+
+        assert_eq!(loader.resolve("http"), Err(InterpreterError::NoMatchingModule { name: String::from("http") }));
+    }
+
+    #[test]
+    fn file_loaders_search_their_directories_in_order() {
+        let root = std::env::temp_dir().join(format!("magi-file-loader-{}", std::process::id()));
+        let first  = root.join("first");
+        let second = root.join("second");
+
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(second.join("net")).unwrap();
+        std::fs::write(first.join("util.mag"), "first").unwrap();
+        std::fs::write(second.join("util.mag"), "second").unwrap();
+        std::fs::write(second.join("net").join("http.mag"), "http").unwrap();
+
+        let loader = FileLoader::new(vec![first.clone(), second.clone()]);
+
+        let util = loader.resolve("util").unwrap();
+        assert_eq!(loader.read(&util), Ok(String::from("first")));
+
+        // Dots in a module name separate directories.
+        let http = loader.resolve("net.http").unwrap();
+        assert_eq!(PathBuf::from(&http), second.join("net").join("http.mag").canonicalize().unwrap());
+
+        assert_eq!(loader.resolve("missing"), Err(InterpreterError::NoMatchingModule { name: String::from("missing") }));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn guards_are_taken_out_of_method_definitions() {
+        let objs = parse("def sign(n Int) where n > 0\n    \"positive\"\nend\nsign(1)", "test").unwrap();
+
+        assert_eq!(objs.len(), 2);
+
+        let crate::types::ObjKind::Expression(method, source) = &objs[0].kind else {
+            panic!("expected a method expression, found {:?}", objs[0]);
+        };
+
+        // The body starts on the next line, so it is still parsed as a block.
+        assert!(matches!(&method.kind, ExpressionKind::Method(method) if matches!(method.body.kind, ExpressionKind::Block(_))));
+        assert!(matches!(source.guard(method).map(|guard| &guard.kind), Some(ExpressionKind::Infix(_))));
+    }
+}
@@ -1,15 +1,27 @@
 pub mod visitors;
+pub mod diagnostic;
+pub mod loader;
+pub mod trace;
+mod builtins;
+mod modules;
 
-use std::collections::{HashMap, BTreeMap};
+use std::collections::HashMap;
 
 use crate::types::{
     Environment,
-    Multimethod,
+    ErrorValue,
+    MatchWarning,
+    Module,
     Obj,
     ObjKind,
+    Source,
     VariablePattern,
 };
 
+use self::diagnostic::Span;
+use self::loader::{FileLoader, ModuleLoader};
+use self::trace::Frame;
+
 use self::visitors::{
     Visitor,
     CallVisitor,
@@ -17,92 +29,260 @@ use self::visitors::{
     MethodVisitor,
     ValueVisitor,
     InfixVisitor,
+    ListVisitor,
+    MatchVisitor,
     PatternVisitor,
+    DefinitionVisitor,
+    AssignmentVisitor,
+    SequenceVisitor,
+    ImportVisitor,
+    ThrowVisitor,
+    TryVisitor,
 };
 
 use magc::type_system::Typed;
 
-use magc::types::{
-    Literal,
-    Expression,
-    ExpressionKind,
-    Method,
-    Call,
-    Infix,
-    Block,
-};
+use magc::types::Expression;
 
+use crate::types::matching;
+use crate::types::obj::nesting;
 use crate::types::pattern::Pattern;
 
 pub type InterpreterResult = Result<Box<Obj>, InterpreterError>;
 
+/// Settings which change how the interpreter behaves.
+#[derive(Debug, Clone)]
+pub struct InterpreterOptions {
+    /// Reject method definitions whose signature already exists instead of replacing the receiver.
+    pub strict: bool,
+    /// How a variable name which occurs more than once in a signature is handled.
+    pub repeated_bindings: RepeatedBindings,
+    /// How an imported multimethod is combined with one of the same name in the importing scope.
+    pub multimethod_merge: MultimethodMerge,
+    /// How deeply expressions may be nested during evaluation before giving up with `TooMuchRecursion`.
+    pub recursion_limit: usize,
+    /// How many expressions a single call to `evaluate` from outside may evaluate before giving up
+    /// with `StepLimitExceeded`, or `None` to never give up.
+    pub step_limit: Option<usize>,
+    /// Import the exports of the `prelude` module into the main program, if the loader finds one.
+    pub prelude: bool,
+}
+
+impl Default for InterpreterOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            repeated_bindings: RepeatedBindings::Equality,
+            multimethod_merge: MultimethodMerge::Combine,
+            recursion_limit: 1000,
+            step_limit: None,
+            prelude: true,
+        }
+    }
+}
+
+/// The ways of handling a variable name which is bound more than once by a pattern, like in `(x, x)`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RepeatedBindings {
+    /// The pattern only matches if all values bound to the name are equal.
+    Equality,
+    /// Patterns with such a name fail with a `DuplicateBinding` error. This applies to method
+    /// signatures, the patterns of `let` definitions, and the arms of `match` and `try` expressions.
+    Reject,
+}
+
+/// The ways of importing a multimethod whose name is already taken by a multimethod in the importing scope.
+///
+/// Receivers added to an imported multimethod are only visible in the importing module and
+/// its own importers. The module which originally defined the multimethod never sees them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MultimethodMerge {
+    /// Both sets of receivers are combined. Two different receivers with the same
+    /// signature and guard are a conflict, which fails with a `ConflictingImport` error.
+    Combine,
+    /// Importing a name which is already defined fails with a `VariableAlreadyExists` error.
+    Reject,
+}
+
 pub struct Interpreter {
+    /// The global scope, which holds every variable not defined inside of a local scope.
     pub environment: Environment,
+    /// The local scopes of the code being evaluated, with the innermost one last.
+    pub scopes: Vec<Environment>,
+    /// The path of the module whose namespace is currently the global environment, or `None` for the main program.
+    pub current_module: Option<String>,
+    /// The global environments of all modules which are not currently being evaluated, by module path.
+    pub namespaces: HashMap<Option<String>, Environment>,
+    /// Every module which has been evaluated by module path, so importing it again reuses its namespace.
+    pub modules: HashMap<String, Module>,
+    /// Code of modules added with `add_module`, which is used instead of asking the loader.
+    pub module_sources: HashMap<String, Vec<Obj>>,
+    /// Finds and reads the source code of imported modules.
+    pub loader: Box<dyn ModuleLoader>,
+    /// The paths of the modules which are being evaluated, with the most recent import last.
+    pub loading: Vec<String>,
+    /// The multimethod calls which are being evaluated, with the innermost call last.
+    pub call_stack: Vec<Frame>,
+    /// Problems found in `match` expressions before they are evaluated, left for the embedder to report.
+    pub warnings: Vec<MatchWarning>,
     pub visitors: HashMap<String, &'static dyn Visitor>,
     pub recursion_level: usize,
+    /// The number of expressions evaluated since the outermost call to `evaluate` started.
+    pub steps: usize,
+    pub options: InterpreterOptions,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        Self::with_options(InterpreterOptions::default())
+    }
+
+    pub fn with_options(options: InterpreterOptions) -> Self {
         let mut visitors = HashMap::new();
 
         visitors.insert("CallExpression".to_string(),        &CallVisitor        as &dyn Visitor);
         visitors.insert("ConditionalExpression".to_string(), &ConditionalVisitor as &dyn Visitor);
         visitors.insert("MethodExpression".to_string(),      &MethodVisitor      as &dyn Visitor);
         visitors.insert("InfixExpression".to_string(),       &InfixVisitor       as &dyn Visitor);
+        visitors.insert("MatchExpression".to_string(),       &MatchVisitor       as &dyn Visitor);
+        visitors.insert("DefinitionExpression".to_string(),  &DefinitionVisitor  as &dyn Visitor);
+        visitors.insert("AssignmentExpression".to_string(),  &AssignmentVisitor  as &dyn Visitor);
+        visitors.insert("SequenceExpression".to_string(),    &SequenceVisitor    as &dyn Visitor);
+        visitors.insert("ImportExpression".to_string(),      &ImportVisitor      as &dyn Visitor);
+        visitors.insert("ThrowExpression".to_string(),       &ThrowVisitor       as &dyn Visitor);
+        visitors.insert("TryExpression".to_string(),         &TryVisitor         as &dyn Visitor);
 
         visitors.insert("Int".to_string(),      &ValueVisitor as &dyn Visitor);
         visitors.insert("UInt".to_string(),     &ValueVisitor as &dyn Visitor);
         visitors.insert("Float".to_string(),    &ValueVisitor as &dyn Visitor);
         visitors.insert("String".to_string(),   &ValueVisitor as &dyn Visitor);
         visitors.insert("Boolean".to_string(), &ValueVisitor as &dyn Visitor);
+        visitors.insert("Error".to_string(),   &ValueVisitor as &dyn Visitor);
+        visitors.insert("List".to_string(),    &ListVisitor  as &dyn Visitor);
 
+        visitors.insert("AlternativePattern".to_string(), &PatternVisitor as &dyn Visitor);
         visitors.insert("FieldPattern".to_string(),    &PatternVisitor as &dyn Visitor);
+        visitors.insert("ListPattern".to_string(),     &PatternVisitor as &dyn Visitor);
+        visitors.insert("NamedPattern".to_string(),    &PatternVisitor as &dyn Visitor);
         visitors.insert("PairPattern".to_string(),     &PatternVisitor as &dyn Visitor);
+        visitors.insert("RangePattern".to_string(),    &PatternVisitor as &dyn Visitor);
+        visitors.insert("RestPattern".to_string(),     &PatternVisitor as &dyn Visitor);
         visitors.insert("TuplePattern".to_string(),    &PatternVisitor as &dyn Visitor);
         visitors.insert("ValuePattern".to_string(),    &PatternVisitor as &dyn Visitor);
         visitors.insert("VariablePattern".to_string(), &PatternVisitor as &dyn Visitor);
 
         Self {
             environment: Environment::new(),
+            scopes: vec![],
+            current_module: None,
+            namespaces: HashMap::new(),
+            modules: HashMap::new(),
+            module_sources: HashMap::new(),
+            loader: Box::new(FileLoader::default()),
+            loading: vec![],
+            call_stack: vec![],
+            warnings: vec![],
             visitors,
             recursion_level: 0,
+            steps: 0,
+            options,
         }
     }
 
+    /// Make sure a pattern binds every name only once, if the options ask for it.
+    pub(crate) fn check_bindings(&self, pattern: &Pattern) -> Result<(), InterpreterError> {
+        match self.options.repeated_bindings {
+            RepeatedBindings::Equality => Ok(()),
+            RepeatedBindings::Reject   => pattern.check_bindings(),
+        }
+    }
+
+    /// Look up a variable in the given environment first, then in the local scopes
+    /// from the innermost outwards, and finally in the global environment.
     pub fn get_variable(&self, variable_pattern: VariablePattern, optional_env: Option<Environment>) -> InterpreterResult {
-        if let Some(env) = optional_env {
+        if let Some(env) = &optional_env {
             if let Some(value) = env.entries.get(&variable_pattern) {
-                Ok(value.clone())
-            } else {
-                Err(InterpreterError::NoMatchingVariable { variable_pattern })
-            }
-        } else {
-            if let Some(value) = self.environment.entries.get(&variable_pattern) {
-                Ok(value.clone())
-            } else {
-                Err(InterpreterError::NoMatchingVariable { variable_pattern })
+                return Ok(value.clone())
             }
         }
+
+        let found = self.scopes
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.environment))
+            .find_map(|scope| scope.entries.get(&variable_pattern));
+
+        if let Some(value) = found {
+            return Ok(value.clone())
+        }
+
+        // A name like `math.sqrt` refers to an export of an imported module.
+        match variable_pattern.name.as_deref().and_then(|name| name.split_once('.')) {
+            Some((module, name)) => self.get_qualified(module, name, optional_env),
+            None                 => Err(InterpreterError::NoMatchingVariable { variable_pattern }),
+        }
     }
 
+    /// Define a new immutable variable in the innermost scope.
     pub fn define_variable(&mut self, variable_pattern: VariablePattern, obj: Obj) -> InterpreterResult {
-        if !self.environment.entries.contains_key(&variable_pattern) {
-            self.environment.entries.insert(variable_pattern.clone(), Box::new(obj));
+        self.insert_variable(variable_pattern, obj, false)
+    }
+
+    /// Define a new variable in the innermost scope which may be assigned a new value later on.
+    pub fn define_mutable_variable(&mut self, variable_pattern: VariablePattern, obj: Obj) -> InterpreterResult {
+        self.insert_variable(variable_pattern, obj, true)
+    }
+
+    fn insert_variable(&mut self, variable_pattern: VariablePattern, obj: Obj, mutable: bool) -> InterpreterResult {
+        let scope = self.current_scope_mut();
+
+        if !scope.entries.contains_key(&variable_pattern) {
+            scope.entries.insert(variable_pattern.clone(), Box::new(obj));
+
+            if mutable {
+                scope.mutable.insert(variable_pattern.clone());
+            }
 
             Ok(Box::new(Obj::new(
                 ObjKind::Pattern(Pattern::Variable(variable_pattern))
             )))
         } else {
-            Err(InterpreterError::VariableAlreadyExists)
+            Err(InterpreterError::VariableAlreadyExists { variable_pattern })
         }
     }
 
+    /// Assign a new value to the variable in the nearest scope which defines it.
     pub fn mutate_variable(&mut self, variable_pattern: VariablePattern, obj: Obj) -> InterpreterResult {
-        if self.environment.entries.contains_key(&variable_pattern) {
-            // Delete the previous entry and recreate it with the new object.
-            self.environment.entries.remove(&variable_pattern);
-            self.environment.entries.insert(variable_pattern.clone(), Box::new(obj));
+        self.replace_variable(variable_pattern, obj, true)
+    }
+
+    /// Replace the value of a variable regardless of whether it is mutable.
+    ///
+    /// This is how a multimethod gets updated when a receiver is added to it,
+    /// which ordinary assignments must not be able to do.
+    pub(crate) fn redefine_variable(&mut self, variable_pattern: VariablePattern, obj: Obj) -> InterpreterResult {
+        self.replace_variable(variable_pattern, obj, false)
+    }
+
+    fn replace_variable(&mut self, variable_pattern: VariablePattern, obj: Obj, check_mutable: bool) -> InterpreterResult {
+        let scope = self.scopes
+            .iter_mut()
+            .rev()
+            .chain(std::iter::once(&mut self.environment))
+            .find(|scope| scope.entries.contains_key(&variable_pattern));
+
+        if let Some(scope) = scope {
+            if check_mutable && !scope.is_mutable(&variable_pattern) {
+                return Err(InterpreterError::AssignToImmutable { variable_pattern })
+            }
+
+            scope.entries.insert(variable_pattern.clone(), Box::new(obj));
 
             Ok(Box::new(Obj::new(
                 ObjKind::Pattern(Pattern::Variable(variable_pattern))
@@ -112,21 +292,76 @@ impl Interpreter {
         }
     }
 
+    /// Return the scope new variables are defined in.
+    pub fn current_scope(&self) -> &Environment {
+        self.scopes.last().unwrap_or(&self.environment)
+    }
+
+    fn current_scope_mut(&mut self) -> &mut Environment {
+        match self.scopes.last_mut() {
+            Some(scope) => scope,
+            None        => &mut self.environment,
+        }
+    }
+
+    /// Evaluate an expression inside of a new innermost scope containing the given variables.
+    pub fn evaluate_in_scope(&mut self, obj: Box<Obj>, env: Environment) -> InterpreterResult {
+        self.scopes.push(env);
+        let result = self.evaluate(obj, None);
+        self.scopes.pop();
+
+        result
+    }
+
+    /// Evaluate the body of a method with the variables bound by its signature.
+    ///
+    /// Methods only see their own scope and the global environment, so the
+    /// local scopes of the caller are put aside until the body is done.
+    pub fn evaluate_in_frame(&mut self, obj: Box<Obj>, env: Environment) -> InterpreterResult {
+        let caller_scopes = std::mem::take(&mut self.scopes);
+        let result = self.evaluate_in_scope(obj, env);
+        self.scopes = caller_scopes;
+
+        result
+    }
+
+    /// Evaluate an expression from the parser, rejecting it if it is nested too deeply.
     pub fn evaluate_expr(
         &mut self,
         // The expression to evaluate.
         expression: Box<Expression>,
+        // The source code the expression was parsed from.
+        source: &Source,
         // An optional environment used for variables in local scope.
         optional_env: Option<Environment>,
     ) -> Result<Box<Obj>, InterpreterError> {
 
+        nesting::check_expression(&expression, 0).map_err(|e| e.at(Span::from(&*expression), self.current_module.clone()))?;
+
+        self.evaluate_checked(expression, source, optional_env)
+    }
+
+    /// Evaluate part of an expression whose nesting has already been checked, like the operand of an infix expression.
+    #[allow(clippy::boxed_local)]
+    pub(crate) fn evaluate_checked(
+        &mut self,
+        expression: Box<Expression>,
+        source: &Source,
+        optional_env: Option<Environment>,
+    ) -> Result<Box<Obj>, InterpreterError> {
+
+        // Malformed literals are reported at the expression they were found in.
+        let span = Span::from(&*expression);
+        let obj  = Obj::from_checked(*expression, source).map_err(|e| e.at(span, self.current_module.clone()))?;
+
         self.evaluate(
-            Box::new(Obj::from(*expression)),
+            Box::new(obj),
             optional_env
         )
     }
 
     /// Interpret a given piece of code and return the result.
+    #[allow(clippy::boxed_local)]
     pub fn evaluate(
         &mut self,
         // The expression to evaluate.
@@ -135,10 +370,47 @@ impl Interpreter {
         optional_env: Option<Environment>,
     ) -> Result<Box<Obj>, InterpreterError> {
 
-        match self.visitors.get(&obj.get_type().unwrap()) {
+        if self.recursion_level >= self.options.recursion_limit {
+            return Err(InterpreterError::TooMuchRecursion)
+        }
+
+        if self.recursion_level == 0 {
+            self.steps = 0;
+
+            // Module bodies are checked when they are loaded.
+            if self.loading.is_empty() {
+                self.warnings.extend(matching::check_all(&obj));
+            }
+        }
+
+        self.steps += 1;
+
+        if self.options.step_limit.is_some_and(|limit| self.steps > limit) {
+            return Err(InterpreterError::StepLimitExceeded)
+        }
+
+        // Errors point at the innermost expression from the source code they were raised in.
+        let span = match &obj.kind {
+            ObjKind::Expression(expression, _) => Some(Span::from(expression)),
+            _                                  => None,
+        };
+
+        self.recursion_level += 1;
+
+        // Expressions without a type have no visitor either.
+        let visitor = obj.get_type().and_then(|type_id| self.visitors.get(&type_id).copied());
+
+        let result = match visitor {
             Some(visitor) => visitor.evaluate(self, optional_env, *obj),
 
             _ => Err(InterpreterError::NoMatchingVisitor),
+        };
+
+        self.recursion_level -= 1;
+
+        match (result, span) {
+            (Err(e), Some(span)) => Err(e.at(span, self.current_module.clone())),
+            (result, _)          => result,
         }
     }
 }
@@ -149,12 +421,562 @@ pub enum InterpreterError {
     UnexpectedType { expected: String, found: Option<String> },
     MethodAlreadyExists,
     SignatureAlreadyExists,
-    VariableAlreadyExists,
+    /// There is no module with the given name.
+    NoMatchingModule { name: String },
+    /// The source code of a module could not be read.
+    ModuleLoadFailed { path: String, message: String },
+    /// The source code of a module is not valid Mag code.
+    ParseFailed { path: String, message: String },
+    /// A module imports itself through the given chain of module paths, which starts and ends with the same module.
+    ImportCycle { chain: Vec<String> },
+    /// The module does not export a variable with the given name.
+    NoMatchingExport { module: String, name: String },
+    /// An imported multimethod has a receiver which conflicts with one of the same name in the importing scope.
+    ConflictingImport { name: String },
+    /// A variable with this name is already defined in the same scope.
+    VariableAlreadyExists { variable_pattern: VariablePattern },
     NoMatchingReceiver,
     NoMatchingMultimethod,
     NoMatchingVariable { variable_pattern: VariablePattern },
+    /// An assignment refers to a variable which is not defined in any enclosing scope.
+    AssignToUndefined { variable_pattern: VariablePattern },
+    /// An assignment refers to a variable which was not defined as mutable.
+    AssignToImmutable { variable_pattern: VariablePattern },
     NoMatchingVisitor,
+    /// None of the arms of a `match` expression matched the given value.
+    NoMatchingArm { value: String },
     /// Raised when the linearization of two patterns fails.
     NoMatch,
+    /// A field name occurs more than once in the same pattern.
+    DuplicateField { name: String },
+    /// A given field does not exist in the reference pattern.
+    UnknownField { name: String },
+    /// A field of the reference pattern without a default value is missing.
+    MissingField { name: String },
+    /// A positional item follows a field in a pattern.
+    PositionalAfterField,
+    /// A variable name is bound more than once by the same pattern.
+    DuplicateBinding { name: String },
+    /// The branches of an alternative pattern do not bind the same variables.
+    InconsistentBindings { expected: Vec<String>, found: Vec<String> },
+    /// A literal from the parser does not have a valid lexeme for its type.
+    InvalidLiteral { expected: String, lexeme: String },
+    /// An integer was divided by zero.
+    DivisionByZero,
+    /// The result of an integer operation does not fit into the type of its left operand.
+    IntegerOverflow,
     TooMuchRecursion,
-}
\ No newline at end of file
+    /// An expression or pattern from the parser is nested more than `MAX_NESTING` levels deep.
+    TooDeeplyNested,
+    /// More expressions were evaluated than the step limit allows.
+    StepLimitExceeded,
+    /// An error value raised by a `throw` expression which was not caught.
+    Thrown { error: ErrorValue },
+    /// Another error along with the location of the expression which raised it.
+    Located { error: Box<InterpreterError>, span: Span, module: Option<String> },
+    /// Another error which escaped from a receiver, along with the call stack at that point.
+    Traced { error: Box<InterpreterError>, trace: Vec<Frame> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use magc::types::{ExpressionKind, Literal};
+    use crate::types::{
+        Assignment,
+        Definition,
+        ErrorValue,
+        FieldPattern,
+        Import,
+        Match,
+        MatchArm,
+        PairPattern,
+        Sequence,
+        Throw,
+        Try,
+        TuplePattern,
+        ValuePattern,
+    };
+
+    fn int(n: i64) -> Box<Obj> {
+        Box::new(Obj::new(ObjKind::Int(n)))
+    }
+
+    fn define(interpreter: &mut Interpreter, definition: Definition) -> InterpreterResult {
+        interpreter.evaluate(Box::new(Obj::new(ObjKind::Definition(definition))), None)
+    }
+
+    fn assign(interpreter: &mut Interpreter, name: &str, value: Box<Obj>) -> InterpreterResult {
+        let assignment = Assignment::new(name.to_string(), value);
+
+        interpreter.evaluate(Box::new(Obj::new(ObjKind::Assignment(assignment))), None)
+    }
+
+    fn variable(name: &str) -> Pattern {
+        Pattern::Variable(VariablePattern::from_name(name.to_string()))
+    }
+
+    #[test]
+    fn bindings_are_immutable_by_default() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::new(variable("x"), int(1))).unwrap();
+
+        assert_eq!(
+            assign(&mut interpreter, "x", int(2)),
+            Err(InterpreterError::AssignToImmutable {
+                variable_pattern: VariablePattern::from_name("x".to_string()),
+            }),
+        );
+    }
+
+    #[test]
+    fn mutable_bindings_can_be_assigned() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::mutable(variable("x"), int(1))).unwrap();
+        assign(&mut interpreter, "x", int(2)).unwrap();
+
+        assert_eq!(
+            interpreter.get_variable(VariablePattern::from_name("x".to_string()), None),
+            Ok(int(2)),
+        );
+    }
+
+    #[test]
+    fn assigning_to_an_undefined_name_fails() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            assign(&mut interpreter, "x", int(1)),
+            Err(InterpreterError::AssignToUndefined {
+                variable_pattern: VariablePattern::from_name("x".to_string()),
+            }),
+        );
+    }
+
+    #[test]
+    fn redefining_in_the_same_scope_fails() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::new(variable("x"), int(1))).unwrap();
+
+        assert_eq!(
+            define(&mut interpreter, Definition::new(variable("x"), int(2))),
+            Err(InterpreterError::VariableAlreadyExists {
+                variable_pattern: VariablePattern::from_name("x".to_string()),
+            }),
+        );
+    }
+
+    fn import(interpreter: &mut Interpreter, import: Import) -> InterpreterResult {
+        interpreter.evaluate(Box::new(Obj::new(ObjKind::Import(import))), None)
+    }
+
+    fn config_module(interpreter: &mut Interpreter) {
+        interpreter.add_module("config", vec![
+            Obj::new(ObjKind::Definition(Definition::new(variable("answer"), int(42)))),
+            Obj::new(ObjKind::Definition(Definition::new(variable("_secret"), int(7)))),
+        ]);
+    }
+
+    #[test]
+    fn qualified_imports_expose_exports_by_module_name() {
+        let mut interpreter = Interpreter::new();
+        config_module(&mut interpreter);
+
+        import(&mut interpreter, Import::qualified("config".to_string(), None)).unwrap();
+
+        assert_eq!(
+            interpreter.get_variable(VariablePattern::from_name("config.answer".to_string()), None),
+            Ok(int(42)),
+        );
+
+        assert_eq!(
+            interpreter.get_variable(VariablePattern::from_name("config._secret".to_string()), None),
+            Err(InterpreterError::NoMatchingExport {
+                module: "config".to_string(),
+                name:   "_secret".to_string(),
+            }),
+        );
+    }
+
+    #[test]
+    fn selective_imports_define_exports_in_the_current_scope() {
+        let mut interpreter = Interpreter::new();
+        config_module(&mut interpreter);
+
+        import(&mut interpreter, Import::selective("config".to_string(), vec!["answer".to_string()])).unwrap();
+
+        assert_eq!(
+            interpreter.get_variable(VariablePattern::from_name("answer".to_string()), None),
+            Ok(int(42)),
+        );
+
+        // The module's namespace stays separate from the importing one.
+        assert!(interpreter.get_variable(VariablePattern::from_name("_secret".to_string()), None).is_err());
+    }
+
+    fn try_catch(interpreter: &mut Interpreter, body: Obj, arms: Vec<MatchArm>) -> InterpreterResult {
+        let try_expression = Try::new(Box::new(body), arms);
+
+        interpreter.evaluate(Box::new(Obj::new(ObjKind::Try(try_expression))), None)
+    }
+
+    fn field(name: &str, value: Pattern) -> Pattern {
+        Pattern::Field(FieldPattern { name: name.to_string(), value: Box::new(value), default: None })
+    }
+
+    #[test]
+    fn thrown_values_are_caught_by_their_fields() {
+        let mut interpreter = Interpreter::new();
+
+        let throw = Obj::new(ObjKind::Throw(Throw::new(Box::new(Obj::new(ObjKind::String("boom".to_string()))))));
+        let error_fields = Pattern::Tuple(TuplePattern {
+            child: Box::new(Pattern::Pair(PairPattern {
+                left:  Box::new(field("kind", variable("kind"))),
+                right: Box::new(Pattern::Pair(PairPattern {
+                    left:  Box::new(field("message", variable("message"))),
+                    right: Box::new(field("payload", variable("payload"))),
+                })),
+            })),
+        });
+
+        let arms = vec![MatchArm {
+            pattern: error_fields,
+            body:    Box::new(Obj::new(ObjKind::Pattern(variable("kind")))),
+        }];
+
+        assert_eq!(
+            try_catch(&mut interpreter, throw, arms),
+            Ok(Box::new(Obj::new(ObjKind::String("Error".to_string())))),
+        );
+    }
+
+    #[test]
+    fn errors_with_a_custom_kind_are_built_by_the_error_constructor() {
+        let mut interpreter = Interpreter::new();
+
+        let mut objs = loader::parse(r#"error("NotFound", "no such user")"#, "test").unwrap();
+        let throw = Obj::new(ObjKind::Throw(Throw::new(Box::new(objs.remove(0)))));
+        let not_found = Pattern::Value(ValuePattern { obj: Box::new(Obj::new(ObjKind::String("NotFound".to_string()))) });
+
+        let arms = vec![MatchArm {
+            pattern: Pattern::Tuple(TuplePattern {
+                child: Box::new(Pattern::Pair(PairPattern {
+                    left:  Box::new(field("kind", not_found)),
+                    right: Box::new(Pattern::Pair(PairPattern {
+                        left:  Box::new(field("message", variable("message"))),
+                        right: Box::new(field("payload", variable("payload"))),
+                    })),
+                })),
+            }),
+            body:    Box::new(Obj::new(ObjKind::Pattern(variable("message")))),
+        }];
+
+        assert_eq!(
+            try_catch(&mut interpreter, throw, arms),
+            Ok(Box::new(Obj::new(ObjKind::String("no such user".to_string())))),
+        );
+    }
+
+    #[test]
+    fn runtime_errors_are_caught_as_error_values() {
+        let mut interpreter = Interpreter::new();
+
+        let missing = Obj::new(ObjKind::Pattern(variable("missing")));
+        let arms = vec![MatchArm {
+            pattern: Pattern::Variable(VariablePattern { name: Some("e".to_string()), type_id: Some("Error".to_string()) }),
+            body:    Box::new(Obj::new(ObjKind::Pattern(variable("e")))),
+        }];
+
+        let caught = try_catch(&mut interpreter, missing.clone(), arms).unwrap();
+
+        match caught.kind {
+            ObjKind::Error(ErrorValue { kind, .. }) => assert_eq!(kind, "NoMatchingVariable"),
+            kind                                    => panic!("expected an error value, found {:?}", kind),
+        }
+
+        // Errors which no arm matches are raised again.
+        assert_eq!(
+            try_catch(&mut interpreter, missing, vec![]),
+            Err(InterpreterError::NoMatchingVariable {
+                variable_pattern: VariablePattern::from_name("missing".to_string()),
+            }),
+        );
+    }
+
+    fn hash(error: &InterpreterError) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        error.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_errors_hash_equally() {
+        let thrown = |payload: ObjKind| InterpreterError::Thrown {
+            error: ErrorValue::new("NotFound".to_string(), "no such user".to_string(), Box::new(Obj::new(payload))),
+        };
+
+        // Objects are compared by their kind, so their differing uuids do not change the hash.
+        assert_eq!(hash(&thrown(ObjKind::Int(1))), hash(&thrown(ObjKind::Int(1))));
+        assert_eq!(hash(&thrown(ObjKind::Pattern(variable("user")))), hash(&thrown(ObjKind::Pattern(variable("user")))));
+
+        assert_ne!(hash(&thrown(ObjKind::Int(1))), hash(&thrown(ObjKind::Int(2))));
+    }
+
+    #[test]
+    fn malformed_literals_are_an_error() {
+        let mut interpreter = Interpreter::new();
+
+        let source = Source::new("99999999999999999999");
+
+        let expression = Expression {
+            kind:      ExpressionKind::Literal(Literal::Int),
+            start_pos: 0,
+            end_pos:   20,
+        };
+
+        let error = interpreter.evaluate_expr(Box::new(expression), &source, None).unwrap_err();
+
+        assert_eq!(error.span(), Some(Span::new(0, 20)));
+        assert_eq!(
+            error.inner(),
+            &InterpreterError::InvalidLiteral {
+                expected: String::from("Int"),
+                lexeme:   String::from("99999999999999999999"),
+            },
+        );
+    }
+
+    #[test]
+    fn evaluation_stops_at_the_step_limit() {
+        let mut interpreter = Interpreter::with_options(InterpreterOptions {
+            step_limit: Some(3),
+            ..InterpreterOptions::default()
+        });
+
+        let sequence = |length| Box::new(Obj::new(ObjKind::Sequence(Sequence::new(vec![*int(1); length]))));
+
+        assert_eq!(interpreter.evaluate(sequence(3), None), Err(InterpreterError::StepLimitExceeded));
+
+        // Every evaluation from outside gets the full budget again.
+        assert_eq!(interpreter.evaluate(sequence(2), None), Ok(int(1)));
+    }
+
+    fn repeated_x() -> Pattern {
+        Pattern::Tuple(TuplePattern {
+            child: Box::new(Pattern::Pair(PairPattern { left: Box::new(variable("x")), right: Box::new(variable("x")) })),
+        })
+    }
+
+    fn ones() -> Box<Obj> {
+        let pair = Pattern::Pair(PairPattern {
+            left:  Box::new(Pattern::Value(ValuePattern { obj: int(1) })),
+            right: Box::new(Pattern::Value(ValuePattern { obj: int(1) })),
+        });
+
+        Box::new(Obj::new(ObjKind::Pattern(Pattern::Tuple(TuplePattern { child: Box::new(pair) }))))
+    }
+
+    fn rejecting() -> Interpreter {
+        Interpreter::with_options(InterpreterOptions {
+            repeated_bindings: RepeatedBindings::Reject,
+            ..InterpreterOptions::default()
+        })
+    }
+
+    #[test]
+    fn repeated_bindings_are_rejected_in_definitions() {
+        let duplicate = Err(InterpreterError::DuplicateBinding { name: "x".to_string() });
+
+        assert_eq!(define(&mut rejecting(), Definition::new(repeated_x(), ones())), duplicate);
+        assert_eq!(define(&mut Interpreter::new(), Definition::new(repeated_x(), ones())), Ok(ones()));
+    }
+
+    #[test]
+    fn repeated_bindings_are_rejected_in_match_and_try_arms() {
+        let arms = || vec![MatchArm { pattern: repeated_x(), body: int(1) }];
+        let duplicate = Err(InterpreterError::DuplicateBinding { name: "x".to_string() });
+
+        let match_expression = Obj::new(ObjKind::Match(Match::new(ones(), arms())));
+
+        assert_eq!(rejecting().evaluate(Box::new(match_expression.clone()), None), duplicate);
+        assert_eq!(Interpreter::new().evaluate(Box::new(match_expression), None), Ok(int(1)));
+
+        // The arms are checked up front, even if the body doesn't fail.
+        assert_eq!(try_catch(&mut rejecting(), *int(2), arms()), duplicate);
+    }
+
+    #[test]
+    fn match_warnings_are_collected_before_evaluation() {
+        let mut interpreter = Interpreter::new();
+
+        let unreachable = || vec![
+            MatchArm { pattern: variable("x"), body: int(1) },
+            MatchArm { pattern: variable("y"), body: int(2) },
+        ];
+        let inner = Obj::new(ObjKind::Match(Match::new(int(0), unreachable())));
+
+        // The inner expression is never evaluated, but it is still checked.
+        let arms = vec![
+            MatchArm { pattern: Pattern::Value(ValuePattern { obj: int(0) }), body: int(3) },
+            MatchArm { pattern: variable("z"), body: Box::new(inner) },
+        ];
+        let outer = Box::new(Obj::new(ObjKind::Match(Match::new(int(0), arms))));
+
+        assert_eq!(interpreter.evaluate(outer, None), Ok(int(3)));
+        assert_eq!(interpreter.warnings, vec![MatchWarning::UnreachableArm { index: 1 }]);
+    }
+
+    #[test]
+    fn match_warnings_are_collected_once_per_module() {
+        let mut interpreter = Interpreter::new();
+
+        let arms = vec![
+            MatchArm { pattern: variable("x"), body: int(1) },
+            MatchArm { pattern: variable("y"), body: int(2) },
+        ];
+        let match_expression = Obj::new(ObjKind::Match(Match::new(int(0), arms)));
+
+        interpreter.add_module("config", vec![
+            Obj::new(ObjKind::Definition(Definition::new(variable("answer"), Box::new(match_expression)))),
+        ]);
+
+        import(&mut interpreter, Import::qualified("config".to_string(), None)).unwrap();
+        import(&mut interpreter, Import::qualified("config".to_string(), Some("settings".to_string()))).unwrap();
+
+        assert_eq!(interpreter.warnings, vec![MatchWarning::UnreachableArm { index: 1 }]);
+    }
+
+    fn tuple(items: [i64; 2]) -> Box<Obj> {
+        let pair = Pattern::Pair(PairPattern {
+            left:  Box::new(Pattern::Value(ValuePattern { obj: int(items[0]) })),
+            right: Box::new(Pattern::Value(ValuePattern { obj: int(items[1]) })),
+        });
+
+        Box::new(Obj::new(ObjKind::Pattern(Pattern::Tuple(TuplePattern { child: Box::new(pair) }))))
+    }
+
+    fn x_and_y() -> Pattern {
+        Pattern::Tuple(TuplePattern {
+            child: Box::new(Pattern::Pair(PairPattern { left: Box::new(variable("x")), right: Box::new(variable("y")) })),
+        })
+    }
+
+    fn lookup(interpreter: &Interpreter, name: &str) -> InterpreterResult {
+        interpreter.get_variable(VariablePattern::from_name(name.to_string()), None)
+    }
+
+    #[test]
+    fn definitions_destructure_their_value() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::new(x_and_y(), tuple([1, 2]))).unwrap();
+
+        assert_eq!(lookup(&interpreter, "x"), Ok(int(1)));
+        assert_eq!(lookup(&interpreter, "y"), Ok(int(2)));
+    }
+
+    #[test]
+    fn failing_definitions_bind_nothing() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::new(variable("y"), int(0))).unwrap();
+
+        assert!(define(&mut interpreter, Definition::new(x_and_y(), tuple([1, 2]))).is_err());
+        assert!(lookup(&interpreter, "x").is_err());
+    }
+
+    #[test]
+    fn sequences_keep_their_definitions_to_themselves() {
+        let mut interpreter = Interpreter::new();
+
+        define(&mut interpreter, Definition::mutable(variable("x"), int(1))).unwrap();
+
+        // `{ let x = 10; x }` shadows the outer variable until the sequence ends.
+        let shadowing = Sequence::new(vec![
+            Obj::new(ObjKind::Definition(Definition::new(variable("x"), int(10)))),
+            Obj::new(ObjKind::Pattern(variable("x"))),
+        ]);
+
+        assert_eq!(interpreter.evaluate(Box::new(Obj::new(ObjKind::Sequence(shadowing))), None), Ok(int(10)));
+        assert_eq!(lookup(&interpreter, "x"), Ok(int(1)));
+
+        // `{ let z = 3; x = 2 }` changes the enclosing variable, while `z` is gone afterwards.
+        let assigning = Sequence::new(vec![
+            Obj::new(ObjKind::Definition(Definition::new(variable("z"), int(3)))),
+            Obj::new(ObjKind::Assignment(Assignment::new("x".to_string(), int(2)))),
+        ]);
+
+        interpreter.evaluate(Box::new(Obj::new(ObjKind::Sequence(assigning))), None).unwrap();
+
+        assert_eq!(lookup(&interpreter, "x"), Ok(int(2)));
+        assert!(lookup(&interpreter, "z").is_err());
+    }
+
+    /// Resolves every module name to the same empty module.
+    struct SharedLoader;
+
+    impl ModuleLoader for SharedLoader {
+        fn resolve(&self, _name: &str) -> Result<String, InterpreterError> {
+            Ok("shared.mag".to_string())
+        }
+
+        fn read(&self, _path: &str) -> Result<String, InterpreterError> {
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn qualified_imports_are_named_after_the_imported_name() {
+        let mut interpreter = Interpreter::new();
+        interpreter.loader = Box::new(SharedLoader);
+
+        import(&mut interpreter, Import::qualified("lib.first".to_string(), None)).unwrap();
+        import(&mut interpreter, Import::qualified("lib.second".to_string(), None)).unwrap();
+
+        // Both names share one cached module, but each import is bound to its own name.
+        assert_eq!(interpreter.modules.len(), 1);
+        assert!(matches!(lookup(&interpreter, "first").map(|obj| obj.kind), Ok(ObjKind::Module(_))));
+        assert!(matches!(lookup(&interpreter, "second").map(|obj| obj.kind), Ok(ObjKind::Module(_))));
+    }
+
+    #[test]
+    fn import_cycles_report_the_whole_chain() {
+        let mut interpreter = Interpreter::new();
+
+        for (name, imported) in [("a", "b"), ("b", "c"), ("c", "a")] {
+            interpreter.add_module(name, vec![Obj::new(ObjKind::Import(Import::qualified(imported.to_string(), None)))]);
+        }
+
+        let error = import(&mut interpreter, Import::qualified("a".to_string(), None)).unwrap_err();
+
+        assert_eq!(
+            error.inner(),
+            &InterpreterError::ImportCycle { chain: vec!["a".to_string(), "b".to_string(), "c".to_string(), "a".to_string()] },
+        );
+
+        // Nothing from the failed import is kept.
+        assert!(interpreter.modules.is_empty());
+        assert!(interpreter.loading.is_empty());
+    }
+
+    #[test]
+    fn the_prelude_does_not_replace_existing_names() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.add_module("prelude", vec![
+            Obj::new(ObjKind::Definition(Definition::new(variable("args"), int(0)))),
+            Obj::new(ObjKind::Definition(Definition::new(variable("answer"), int(42)))),
+        ]);
+
+        define(&mut interpreter, Definition::new(variable("args"), int(1))).unwrap();
+        interpreter.load_prelude().unwrap();
+
+        assert_eq!(lookup(&interpreter, "args"), Ok(int(1)));
+        assert_eq!(lookup(&interpreter, "answer"), Ok(int(42)));
+    }
+}
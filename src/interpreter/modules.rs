@@ -0,0 +1,235 @@
+//! Evaluating modules and moving variables between their namespaces.
+
+use std::collections::BTreeSet;
+
+use crate::types::matching;
+use crate::types::{
+    Environment,
+    Module,
+    Obj,
+    ObjKind,
+    VariablePattern,
+};
+
+use super::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+    MultimethodMerge,
+};
+
+use magc::type_system::Typed;
+
+/// The name of the module whose exports are available to every program.
+const PRELUDE: &str = "prelude";
+
+impl Interpreter {
+    /// Make some code available for importing under the given module name.
+    pub fn add_module(&mut self, name: &str, body: Vec<Obj>) {
+        self.module_sources.insert(name.to_string(), body);
+    }
+
+    /// Return the module with the given name, evaluating it first if it hasn't been imported before.
+    ///
+    /// Modules are cached by the path the loader resolves them to. Each one is evaluated in a
+    /// fresh namespace, so it only sees its own definitions and the ones it imports itself.
+    /// If evaluation fails, nothing is kept and the next import tries again.
+    pub fn load_module(&mut self, name: &str) -> Result<Module, InterpreterError> {
+        let path = if self.module_sources.contains_key(name) {
+            name.to_string()
+        } else {
+            self.loader.resolve(name)?
+        };
+
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone())
+        }
+
+        // A module which is still being evaluated can't be imported again.
+        if let Some(start) = self.loading.iter().position(|loading| *loading == path) {
+            let mut chain = self.loading[start..].to_vec();
+            chain.push(path);
+
+            return Err(InterpreterError::ImportCycle { chain })
+        }
+
+        let body = match self.module_sources.get(name) {
+            Some(body) => body.clone(),
+            None       => {
+                let source = self.loader.read(&path)?;
+                super::loader::parse(&source, &path)?
+            },
+        };
+
+        self.loading.push(path.clone());
+        let result = self.evaluate_module(&path, body);
+        self.loading.pop();
+
+        let namespace = result?;
+
+        let exports: BTreeSet<String> = namespace.entries
+            .keys()
+            .filter_map(|variable_pattern| variable_pattern.name.clone())
+            .filter(|name| Module::is_exported_name(name))
+            .collect();
+
+        let module = Module::new(name.to_string(), path.clone(), exports);
+
+        self.namespaces.insert(Some(path.clone()), namespace);
+        self.modules.insert(path, module.clone());
+
+        Ok(module)
+    }
+
+    /// Evaluate the body of a module in a new namespace and return that namespace.
+    fn evaluate_module(&mut self, path: &str, body: Vec<Obj>) -> Result<Environment, InterpreterError> {
+        let module_id = Some(path.to_string());
+        self.namespaces.insert(module_id.clone(), Environment::new());
+
+        let caller_scopes = std::mem::take(&mut self.scopes);
+        let previous = self.enter_module(module_id.clone());
+
+        for obj in &body {
+            self.warnings.extend(matching::check_all(obj));
+        }
+
+        let mut result = Ok(());
+
+        for obj in body {
+            if let Err(e) = self.evaluate(Box::new(obj), None) {
+                result = Err(e);
+                break
+            }
+        }
+
+        self.enter_module(previous);
+        self.scopes = caller_scopes;
+
+        let namespace = self.namespaces.remove(&module_id).unwrap_or_default();
+
+        result.map(|_| namespace)
+    }
+
+    /// Import every export of the `prelude` module into the current scope.
+    ///
+    /// Nothing happens if the prelude is disabled in the options or the loader can't find it.
+    /// Names which are already defined, like the `args` of a script, are kept and not imported.
+    pub fn load_prelude(&mut self) -> Result<(), InterpreterError> {
+        if !self.options.prelude {
+            return Ok(())
+        }
+
+        let module = match self.load_module(PRELUDE) {
+            Ok(module) => module,
+
+            // Programs don't depend on a prelude, so a missing one is fine.
+            Err(InterpreterError::NoMatchingModule { name }) if name == PRELUDE => return Ok(()),
+
+            Err(e) => return Err(e),
+        };
+
+        for name in module.exports.clone() {
+            if self.current_scope().entries.contains_key(&VariablePattern::from_name(name.clone())) {
+                continue
+            }
+
+            let value = self.get_export(&module, &name)?;
+            self.import_variable(name, *value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Make the namespace of the given module the global environment and return the previous module.
+    ///
+    /// Passing the returned module back in restores the previous namespace.
+    pub fn enter_module(&mut self, module: Option<String>) -> Option<String> {
+        if module == self.current_module {
+            return module
+        }
+
+        let namespace = self.namespaces.remove(&module).unwrap_or_default();
+        let previous_namespace = std::mem::replace(&mut self.environment, namespace);
+
+        self.namespaces.insert(self.current_module.clone(), previous_namespace);
+
+        std::mem::replace(&mut self.current_module, module)
+    }
+
+    /// Look up an exported variable of a module, following nested modules for names like `a.b`.
+    pub fn get_export(&self, module: &Module, name: &str) -> InterpreterResult {
+        let (head, rest) = match name.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None               => (name, None),
+        };
+
+        let no_matching_export = || InterpreterError::NoMatchingExport {
+            module: module.name.clone(),
+            name:   head.to_string(),
+        };
+
+        if !module.exports.contains(head) {
+            return Err(no_matching_export())
+        }
+
+        let value = self
+            .namespace(&Some(module.path.clone()))
+            .and_then(|namespace| namespace.entries.get(&VariablePattern::from_name(head.to_string())))
+            .cloned()
+            .ok_or_else(no_matching_export)?;
+
+        match rest {
+            Some(rest) => self.get_export(&self::expect_module(*value)?, rest),
+            None       => Ok(value),
+        }
+    }
+
+    /// Resolve a qualified name, where the first part names a module in scope.
+    pub(crate) fn get_qualified(&self, module: &str, name: &str, optional_env: Option<Environment>) -> InterpreterResult {
+        let obj = self.get_variable(VariablePattern::from_name(module.to_string()), optional_env)?;
+
+        self.get_export(&self::expect_module(*obj)?, name)
+    }
+
+    /// Define an imported variable in the innermost scope, following the rules for merging multimethods.
+    pub fn import_variable(&mut self, name: String, obj: Obj) -> InterpreterResult {
+        let variable_pattern = VariablePattern::from_name(name.clone());
+        let existing = self.current_scope().entries.get(&variable_pattern).cloned();
+
+        match (existing.map(|existing| existing.kind), obj.kind) {
+            (None, kind) => self.define_variable(variable_pattern, Obj::new(kind)),
+
+            (Some(ObjKind::Multimethod(existing)), ObjKind::Multimethod(imported))
+                if self.options.multimethod_merge == MultimethodMerge::Combine => {
+
+                let merged = match existing.merge(&imported) {
+                    Ok(merged) => merged,
+                    Err(_)     => return Err(InterpreterError::ConflictingImport { name }),
+                };
+
+                self.redefine_variable(variable_pattern, Obj::new(ObjKind::Multimethod(merged)))
+            },
+
+            _ => Err(InterpreterError::VariableAlreadyExists { variable_pattern }),
+        }
+    }
+
+    fn namespace(&self, module: &Option<String>) -> Option<&Environment> {
+        if *module == self.current_module {
+            Some(&self.environment)
+        } else {
+            self.namespaces.get(module)
+        }
+    }
+}
+
+fn expect_module(obj: Obj) -> Result<Module, InterpreterError> {
+    match obj.kind {
+        ObjKind::Module(module) => Ok(module),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("Module"),
+            found: obj.get_type(),
+        }),
+    }
+}
@@ -0,0 +1,35 @@
+//! The call stack of the Mag program being evaluated.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::diagnostic::Span;
+
+/// A call of a multimethod whose selected receiver is being evaluated.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Frame {
+    /// The name of the called multimethod.
+    pub name:      String,
+    /// The signature of the receiver which handles the call, printed in Mag syntax.
+    pub signature: Option<String>,
+    /// The location of the call expression, if it came from source code.
+    pub call_site: Option<Span>,
+    /// The path of the module the call is made from, or `None` for the main program.
+    pub module:    Option<String>,
+}
+
+impl Frame {
+    pub fn new(name: String, signature: Option<String>, call_site: Option<Span>, module: Option<String>) -> Self {
+        Self {
+            name,
+            signature,
+            call_site,
+            module,
+        }
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name, self.signature.as_deref().unwrap_or_default())
+    }
+}
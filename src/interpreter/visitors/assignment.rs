@@ -0,0 +1,53 @@
+use crate::types::{
+    Assignment,
+    Environment,
+    Obj,
+    ObjKind,
+    VariablePattern,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct AssignmentVisitor;
+
+impl Visitor for AssignmentVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let assignment = self::expect_assignment(obj)?;
+        let value = interpreter.evaluate(assignment.value, optional_env)?;
+
+        // Only variables in a scope can be changed, not the ones in the given environment.
+        match interpreter.mutate_variable(VariablePattern::from_name(assignment.name), *value.clone()) {
+            Ok(_) => Ok(value),
+
+            Err(InterpreterError::NoMatchingVariable { variable_pattern }) => {
+                Err(InterpreterError::AssignToUndefined { variable_pattern })
+            },
+
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn expect_assignment(obj: Obj) -> Result<Assignment, InterpreterError> {
+    match obj.kind {
+        ObjKind::Assignment(assignment) => Ok(assignment),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("AssignmentExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
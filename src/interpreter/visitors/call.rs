@@ -1,15 +1,18 @@
-use crate::types::{Environment, Obj, ObjKind, VariablePattern, Multimethod, Pattern};
+use crate::types::{Environment, Obj, ObjKind, VariablePattern, Multimethod, Source};
+use crate::types::multimethod;
 use super::{
     Visitor,
 };
 
+use crate::interpreter::builtins;
+use crate::interpreter::diagnostic::Span;
 use crate::interpreter::{
     Interpreter,
     InterpreterResult,
     InterpreterError,
 };
 
-use magc::types::{Call, Expression, ExpressionKind, Literal};
+use magc::types::{Call, ExpressionKind};
 use magc::type_system::Typed;
 
 pub struct CallVisitor;
@@ -22,19 +25,35 @@ impl Visitor for CallVisitor {
         obj: Obj,
     ) -> InterpreterResult {
 
-        let call = self::expect_call(obj)?;
-        let variable = interpreter.get_variable(VariablePattern::from_name(call.name.clone()), None)?;
+        let call_site = match &obj.kind {
+            ObjKind::Expression(expression, _) => Some(Span::from(expression)),
+            _                                  => None,
+        };
+
+        let (call, source) = self::expect_call(obj)?;
+
+        let variable = match interpreter.get_variable(VariablePattern::from_name(call.name.clone()), None) {
+            Ok(variable) => variable,
+
+            Err(InterpreterError::NoMatchingVariable { .. }) if builtins::is_constructor(&call.name) => {
+                let arguments = multimethod::evaluate_arguments(interpreter, call.signature, &source, optional_env)?;
+
+                return Ok(Box::new(builtins::construct(&call.name, arguments)?))
+            },
+
+            Err(e) => return Err(e),
+        };
 
         self::expect_multimethod(*variable)?
-            .call(interpreter, call.signature, optional_env)
+            .call(interpreter, call.signature, &source, optional_env, call_site)
     }
 }
 
-fn expect_call(obj: Obj) -> Result<Call, InterpreterError> {
+fn expect_call(obj: Obj) -> Result<(Call, Source), InterpreterError> {
     match obj.kind {
-        ObjKind::Expression(expression) => {
+        ObjKind::Expression(expression, source) => {
             if let ExpressionKind::Call(call) = expression.kind {
-                Ok(call)
+                Ok((call, source))
             } else {
                 Err(InterpreterError::UnexpectedType {
                     expected: String::from("CallExpression"),
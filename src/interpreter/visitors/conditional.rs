@@ -1,5 +1,4 @@
 use crate::types::*;
-use crate::types::ObjKind::*;
 use super::Visitor;
 
 use crate::interpreter::{
@@ -10,11 +9,8 @@ use crate::interpreter::{
 
 use magc::type_system::Typed;
 use magc::types::{
-    Expression,
     ExpressionKind,
     Conditional,
-    Token,
-    TokenKind,
 };
 
 pub struct ConditionalVisitor;
@@ -27,33 +23,35 @@ impl Visitor for ConditionalVisitor {
         obj: Obj,
     ) -> InterpreterResult {
 
-        let conditional = self::expect_conditional(obj)?;
+        let (conditional, source) = self::expect_conditional(obj)?;
 
-        if interpreter.evaluate_expr(conditional.condition, optional_env.clone())?.is_truthy() {
-            interpreter.evaluate_expr(conditional.then_arm, optional_env)
+        if interpreter.evaluate_checked(conditional.condition, &source, optional_env.clone())?.is_truthy() {
+            interpreter.evaluate_checked(conditional.then_arm, &source, optional_env)
+        } else if let Some(else_arm) = conditional.else_arm {
+            interpreter.evaluate_checked(else_arm, &source, optional_env)
         } else {
             Ok(Box::new(Obj::new(ObjKind::Nothing)))
         }
     }
 }
 
-fn expect_conditional(obj: Obj) -> Result<Conditional, InterpreterError> {
+fn expect_conditional(obj: Obj) -> Result<(Conditional, Source), InterpreterError> {
     let found = obj.get_type();
 
     match obj.kind {
-        ObjKind::Expression(expression) => {
+        ObjKind::Expression(expression, source) => {
             if let ExpressionKind::Conditional(conditional) = expression.kind {
-                Ok(conditional)
+                Ok((conditional, source))
             } else {
                 Err(InterpreterError::UnexpectedType {
-                    expected: format!("InfixExpression"),
+                    expected: String::from("ConditionalExpression"),
                     found,
                 })
             }
         },
 
         _ => Err(InterpreterError::UnexpectedType {
-            expected: format!("ConditionalExpression"),
+            expected: String::from("ConditionalExpression"),
             found,
         }),
     }
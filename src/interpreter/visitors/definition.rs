@@ -0,0 +1,68 @@
+use crate::types::{
+    Definition,
+    Environment,
+    Obj,
+    ObjKind,
+    Pattern,
+    ValuePattern,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct DefinitionVisitor;
+
+impl Visitor for DefinitionVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let definition = self::expect_definition(obj)?;
+        interpreter.check_bindings(&definition.pattern)?;
+
+        let value = interpreter.evaluate(definition.value, optional_env)?;
+
+        let bindings = definition.pattern.linearize(
+            Pattern::Value(ValuePattern { obj: value.clone() })
+        )?;
+
+        // Check every name first, so a failing definition doesn't leave some of its variables behind.
+        let scope = interpreter.current_scope();
+
+        if let Some(variable_pattern) = bindings.entries.keys().find(|name| scope.entries.contains_key(*name)) {
+            return Err(InterpreterError::VariableAlreadyExists {
+                variable_pattern: variable_pattern.clone(),
+            })
+        }
+
+        for (variable_pattern, obj) in bindings.entries {
+            if definition.mutable {
+                interpreter.define_mutable_variable(variable_pattern, *obj)?;
+            } else {
+                interpreter.define_variable(variable_pattern, *obj)?;
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+fn expect_definition(obj: Obj) -> Result<Definition, InterpreterError> {
+    match obj.kind {
+        ObjKind::Definition(definition) => Ok(definition),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("DefinitionExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
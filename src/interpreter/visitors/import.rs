@@ -0,0 +1,73 @@
+use crate::types::{
+    Environment,
+    Import,
+    ImportKind,
+    Obj,
+    ObjKind,
+    VariablePattern,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct ImportVisitor;
+
+impl Visitor for ImportVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        _optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let import = self::expect_import(obj)?;
+        let module = interpreter.load_module(&import.module)?;
+
+        match import.kind {
+            ImportKind::Qualified { alias } => {
+                // Modules are cached by path, so the cached name may be a different one resolving to the same module.
+                let name = alias.unwrap_or_else(|| {
+                    import.module.rsplit('.').next().unwrap_or(&import.module).to_string()
+                });
+
+                interpreter.define_variable(
+                    VariablePattern::from_name(name),
+                    Obj::new(ObjKind::Module(module.clone())),
+                )?;
+            },
+
+            ImportKind::Selective { names } => {
+                // Look up every name first, so a missing export doesn't leave a partial import behind.
+                let mut values = vec![];
+
+                for name in names {
+                    let value = interpreter.get_export(&module, &name)?;
+                    values.push((name, value));
+                }
+
+                for (name, value) in values {
+                    interpreter.import_variable(name, *value)?;
+                }
+            },
+        }
+
+        Ok(Box::new(Obj::new(ObjKind::Module(module))))
+    }
+}
+
+fn expect_import(obj: Obj) -> Result<Import, InterpreterError> {
+    match obj.kind {
+        ObjKind::Import(import) => Ok(import),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("ImportExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
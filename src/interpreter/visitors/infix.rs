@@ -1,5 +1,5 @@
 use crate::types::*;
-use crate::types::ObjKind::*;
+use crate::types::obj::Operator;
 use super::Visitor;
 
 use crate::interpreter::{
@@ -10,11 +10,8 @@ use crate::interpreter::{
 
 use magc::type_system::Typed;
 use magc::types::{
-    Expression,
     ExpressionKind,
     Infix,
-    Token,
-    TokenKind,
 };
 
 pub struct InfixVisitor;
@@ -27,39 +24,36 @@ impl Visitor for InfixVisitor {
         obj: Obj,
     ) -> InterpreterResult {
 
-        let infix = self::expect_infix_expression(obj)?;
+        let (infix, source) = self::expect_infix_expression(obj)?;
         
-        let left = interpreter.evaluate_expr(infix.left, optional_env.clone())?;
-        let right = interpreter.evaluate_expr(infix.right, optional_env)?;
+        let left = interpreter.evaluate_checked(infix.left, &source, optional_env.clone())?;
+        let right = interpreter.evaluate_checked(infix.right, &source, optional_env)?;
 
-        match infix.operator.kind {
-            TokenKind::Plus  => Ok(Box::new((*left + *right)?)),
-            TokenKind::Minus => Ok(Box::new((*left - *right)?)),
-            TokenKind::Slash => Ok(Box::new((*left / *right)?)),
-            TokenKind::Star  => Ok(Box::new((*left * *right)?)),
+        match Operator::from_token(&infix.operator.kind) {
+            Some(operator) => Ok(Box::new(operator.apply(*left, *right)?)),
 
-            _ => Err(InterpreterError::Unimplemented),
+            None => Err(InterpreterError::Unimplemented),
         }
     }
 }
 
-fn expect_infix_expression(obj: Obj) -> Result<Infix, InterpreterError> {
+fn expect_infix_expression(obj: Obj) -> Result<(Infix, Source), InterpreterError> {
     let found = obj.get_type();
 
     match obj.kind {
-        ObjKind::Expression(expression) => {
+        ObjKind::Expression(expression, source) => {
             if let ExpressionKind::Infix(method) = expression.kind {
-                Ok(method)
+                Ok((method, source))
             } else {
                 Err(InterpreterError::UnexpectedType {
-                    expected: format!("InfixExpression"),
+                    expected: String::from("InfixExpression"),
                     found,
                 })
             }
         },
 
         _ => Err(InterpreterError::UnexpectedType {
-            expected: format!("InfixExpression"),
+            expected: String::from("InfixExpression"),
             found,
         }),
     }
@@ -0,0 +1,40 @@
+use crate::types::{Environment, Obj};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct ListVisitor;
+
+impl Visitor for ListVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let items = match obj.list_items() {
+            Some(items) => items,
+
+            None => return Err(InterpreterError::UnexpectedType {
+                expected: String::from("List"),
+                found: obj.get_type(),
+            }),
+        };
+
+        // Evaluate every item so the resulting list only contains values.
+        let mut values = vec![];
+
+        for item in items {
+            values.push(*interpreter.evaluate(Box::new(item), optional_env.clone())?);
+        }
+
+        Ok(Box::new(Obj::list(values)))
+    }
+}
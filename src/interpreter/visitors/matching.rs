@@ -0,0 +1,64 @@
+use crate::types::{
+    Environment,
+    Match,
+    Obj,
+    ObjKind,
+    Pattern,
+    ValuePattern,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct MatchVisitor;
+
+impl Visitor for MatchVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let match_expression = self::expect_match(obj)?;
+
+        for arm in &match_expression.arms {
+            interpreter.check_bindings(&arm.pattern)?;
+        }
+
+        let value = interpreter.evaluate(match_expression.value, optional_env.clone())?;
+
+        let given = Pattern::Value(ValuePattern { obj: value.clone() });
+
+        for arm in match_expression.arms {
+            if let Ok(bindings) = arm.pattern.linearize(given.clone()) {
+                // The variables bound by the pattern are only visible inside of the arm.
+                let env = match &optional_env {
+                    Some(env) => env.extend(bindings),
+                    None      => bindings,
+                };
+
+                return interpreter.evaluate_in_scope(arm.body, env)
+            }
+        }
+
+        Err(InterpreterError::NoMatchingArm { value: value.to_string() })
+    }
+}
+
+fn expect_match(obj: Obj) -> Result<Match, InterpreterError> {
+    match obj.kind {
+        ObjKind::Match(match_expression) => Ok(match_expression),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("MatchExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
@@ -1,5 +1,5 @@
-use crate::types::{Environment, Obj, ObjKind, Pattern, VariablePattern};
-use crate::types::Multimethod;
+use crate::types::{Environment, Obj, ObjKind, Pattern, Source, VariablePattern};
+use crate::types::{Multimethod, Receiver};
 use super::Visitor;
 
 use crate::interpreter::{
@@ -8,7 +8,7 @@ use crate::interpreter::{
     InterpreterError,
 };
 
-use magc::types::{Method, Expression, ExpressionKind, Literal};
+use magc::types::{Method, Expression, ExpressionKind};
 use magc::types::Pattern as MagcPattern;
 use magc::type_system::Typed;
 
@@ -22,65 +22,93 @@ impl Visitor for MethodVisitor {
         obj: Obj,
     ) -> InterpreterResult {
 
-        let method = self::expect_method(obj)?;
+        let (method, guard, source) = self::expect_method(obj)?;
         let pattern   = VariablePattern { name: Some(method.name.clone()), type_id: None };
 
-        match interpreter.get_variable(pattern.clone(), optional_env) {
+        // Default values of optional fields are evaluated once, in the scope of the definition.
+        let signature = match self::pattern_or_none(method.signature, &source)? {
+            Some(pattern) => Some(pattern.evaluate_defaults(interpreter, optional_env.clone())?),
+            None          => None,
+        };
+
+        if let Some(pattern) = &signature {
+            interpreter.check_bindings(pattern)?;
+        }
+
+        let guard = match guard {
+            Some(guard) => Some(Box::new(Obj::from_checked(guard, &source)?)),
+            None        => None,
+        };
+
+        // The receiver remembers its module, so its guard and body can see the module's other definitions.
+        let receiver = Receiver::with_guard(signature, guard, Box::new(Obj::from_checked(*method.body, &source)?))
+            .in_module(interpreter.current_module.clone());
+
+        let redefined = match interpreter.get_variable(pattern.clone(), optional_env) {
             // There is already a multimethod with this name, so try to insert the new receiver.
             Ok(obj) => {
                 let mut multimethod = self::expect_multimethod(*obj)?;
 
-                // Add the new receiver to the multimethod.
-                multimethod.define(
-                    self::pattern_or_none(method.signature),
-                    Box::new(Obj::from(*method.body)),
-                )?;
+                if interpreter.options.strict && multimethod.has_receiver(&receiver.signature, &receiver.guard) {
+                    return Err(InterpreterError::MethodAlreadyExists)
+                }
+
+                // Add the new receiver to the multimethod, replacing any previous one with the same signature.
+                let previous = multimethod.define_receiver(receiver);
 
-                interpreter.mutate_variable(
+                // Multimethods are immutable to assignments, but not to new receivers.
+                interpreter.redefine_variable(
                     pattern,
                     Obj::new(ObjKind::Multimethod(multimethod)),
                 )?;
+
+                previous.is_some()
             },
 
             // There is no multimethod definition with the given name, so create a new one.
             Err(InterpreterError::NoMatchingVariable { .. }) => {
 
-                let multimethod = Multimethod::from(
-                    self::pattern_or_none(method.signature),
-                    Box::new(Obj::from(*method.body)),
-                );
+                let multimethod = Multimethod::from_receiver(method.name.clone(), receiver);
 
                 // Create a new multimethod with the given receiver and register it in the interpreter.
                 interpreter.define_variable(
                     pattern,
                     Obj::new(ObjKind::Multimethod(multimethod)),
                 )?;
+
+                false
             },
 
             Err(e) => return Err(e),
         };
 
+        // Let the caller know whether an existing receiver was replaced.
+        let type_id = if redefined { "Redefinition" } else { "Method" };
+
         Ok(Box::new(
-            Obj::new(ObjKind::Type("Method".to_string()))
+            Obj::new(ObjKind::Type(type_id.to_string()))
         ))
     }
 }
 
-fn pattern_or_none(pattern: Option<MagcPattern>) -> Option<Pattern> {
+fn pattern_or_none(pattern: Option<MagcPattern>, source: &Source) -> Result<Option<Pattern>, InterpreterError> {
     if let Some(p) = pattern {
-        Some(Pattern::from(p))
+        Ok(Some(Pattern::from_checked(p, source)?))
     } else {
-        None
+        Ok(None)
     }
 }
 
-fn expect_method(obj: Obj) -> Result<Method, InterpreterError> {
+/// Return the method definition in the given object, along with its guard clause.
+fn expect_method(obj: Obj) -> Result<(Method, Option<Expression>, Source), InterpreterError> {
     let found = obj.get_type();
 
     match obj.kind {
-        ObjKind::Expression(expression) => {
+        ObjKind::Expression(expression, source) => {
+            let guard = source.guard(&expression).cloned();
+
             if let ExpressionKind::Method(method) = expression.kind {
-                Ok(method)
+                Ok((method, guard, source))
             } else {
                 Err(InterpreterError::UnexpectedType {
                     expected: String::from("MethodExpression"),
@@ -3,21 +3,36 @@ mod conditional;
 mod method;
 mod value;
 mod infix;
+mod list;
+mod matching;
 mod pattern;
+mod definition;
+mod assignment;
+mod sequence;
+mod import;
+mod throw;
+mod try_catch;
 
 pub use self::call::*;
 pub use self::conditional::*;
 pub use self::method::*;
 pub use self::value::*;
 pub use self::infix::*;
+pub use self::list::*;
+pub use self::matching::*;
 pub use self::pattern::*;
+pub use self::definition::*;
+pub use self::assignment::*;
+pub use self::sequence::*;
+pub use self::import::*;
+pub use self::throw::*;
+pub use self::try_catch::*;
 
 use crate::types::{Environment, Obj};
 use crate::interpreter::{
     Interpreter,
     InterpreterResult,
 };
-use magc::types::Expression;
 
 /// A piece of code that knows how to evaluate a specific kind of expression.
 pub trait Visitor {
@@ -4,12 +4,12 @@ use crate::types::{
     ObjKind,
     Pattern,
     FieldPattern,
+    ListPattern,
     PairPattern,
     TuplePattern,
     ValuePattern,
     VariablePattern,
 };
-use crate::types::Multimethod;
 use super::Visitor;
 
 use crate::interpreter::{
@@ -18,7 +18,6 @@ use crate::interpreter::{
     InterpreterError,
 };
 
-use magc::types::{Method, Expression, ExpressionKind, Literal};
 use magc::type_system::Typed;
 
 pub struct PatternVisitor;
@@ -41,11 +40,53 @@ impl Visitor for PatternVisitor {
                 }
             ))))),
 
-            _ => Ok(Box::new(Obj::new(ObjKind::Pattern(pattern))))
+            _ => Ok(Box::new(Obj::new(ObjKind::Pattern(
+                self::evaluate_item(interpreter, pattern, optional_env)?
+            ))))
         }
     }
 }
 
+/// Evaluate the values and variables nested inside a structured pattern.
+///
+/// Variables are replaced with value patterns containing their current value, so the
+/// result only consists of values which can be matched against a signature.
+fn evaluate_item(interpreter: &mut Interpreter, pattern: Pattern, optional_env: Option<Environment>) -> Result<Pattern, InterpreterError> {
+    Ok(match pattern {
+        Pattern::Field(FieldPattern { name, value, default }) => Pattern::Field(FieldPattern {
+            name,
+            value: Box::new(self::evaluate_item(interpreter, *value, optional_env)?),
+            default,
+        }),
+
+        Pattern::List(ListPattern { child }) => Pattern::List(ListPattern {
+            child: match child {
+                Some(child) => Some(Box::new(self::evaluate_item(interpreter, *child, optional_env)?)),
+                None        => None,
+            },
+        }),
+
+        Pattern::Pair(PairPattern { left, right }) => Pattern::Pair(PairPattern {
+            left:  Box::new(self::evaluate_item(interpreter, *left, optional_env.clone())?),
+            right: Box::new(self::evaluate_item(interpreter, *right, optional_env)?),
+        }),
+
+        Pattern::Tuple(TuplePattern { child }) => Pattern::Tuple(TuplePattern {
+            child: Box::new(self::evaluate_item(interpreter, *child, optional_env)?),
+        }),
+
+        Pattern::Value(ValuePattern { obj }) => Pattern::Value(ValuePattern {
+            obj: interpreter.evaluate(obj, optional_env)?,
+        }),
+
+        Pattern::Variable(variable_pattern @ VariablePattern { name: Some(_), .. }) => Pattern::Value(ValuePattern {
+            obj: interpreter.get_variable(variable_pattern, optional_env)?,
+        }),
+
+        pattern => pattern,
+    })
+}
+
 fn expect_pattern(obj: Obj) -> Result<Pattern, InterpreterError> {
     match obj.kind {
         ObjKind::Pattern(pattern) => Ok(pattern),
@@ -0,0 +1,64 @@
+use crate::types::{
+    Environment,
+    Obj,
+    ObjKind,
+    Sequence,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct SequenceVisitor;
+
+impl Visitor for SequenceVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let sequence = self::expect_sequence(obj)?;
+
+        let depth = interpreter.scopes.len();
+
+        // The given variables get a scope of their own, so they can still be
+        // shadowed by definitions inside of the sequence.
+        if let Some(env) = optional_env {
+            interpreter.scopes.push(env);
+        }
+
+        interpreter.scopes.push(Environment::empty());
+
+        let mut result = Ok(Box::new(Obj::new(ObjKind::Nothing)));
+
+        for expression in sequence.expressions {
+            result = interpreter.evaluate(Box::new(expression), None);
+
+            if result.is_err() {
+                break
+            }
+        }
+
+        interpreter.scopes.truncate(depth);
+
+        result
+    }
+}
+
+fn expect_sequence(obj: Obj) -> Result<Sequence, InterpreterError> {
+    match obj.kind {
+        ObjKind::Sequence(sequence) => Ok(sequence),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("SequenceExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
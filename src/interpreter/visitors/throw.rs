@@ -0,0 +1,50 @@
+use crate::types::{
+    Environment,
+    ErrorValue,
+    Obj,
+    ObjKind,
+    Throw,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct ThrowVisitor;
+
+impl Visitor for ThrowVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let throw = self::expect_throw(obj)?;
+        let value = interpreter.evaluate(throw.value, optional_env)?;
+
+        let error = match &value.kind {
+            ObjKind::Error(error)    => error.clone(),
+            ObjKind::String(message) => ErrorValue::new(String::from("Error"), message.clone(), value.clone()),
+            _                        => ErrorValue::new(String::from("Error"), value.to_string(), value),
+        };
+
+        Err(InterpreterError::Thrown { error })
+    }
+}
+
+fn expect_throw(obj: Obj) -> Result<Throw, InterpreterError> {
+    match obj.kind {
+        ObjKind::Throw(throw) => Ok(throw),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("ThrowExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
@@ -0,0 +1,75 @@
+use crate::types::{
+    Environment,
+    ErrorValue,
+    Obj,
+    ObjKind,
+    Pattern,
+    Try,
+    ValuePattern,
+};
+use super::Visitor;
+
+use crate::interpreter::{
+    Interpreter,
+    InterpreterResult,
+    InterpreterError,
+};
+
+use magc::type_system::Typed;
+
+pub struct TryVisitor;
+
+impl Visitor for TryVisitor {
+    fn evaluate(
+        &self,
+        interpreter: &mut Interpreter,
+        optional_env: Option<Environment>,
+        obj: Obj,
+    ) -> InterpreterResult {
+
+        let try_expression = self::expect_try(obj)?;
+
+        for arm in &try_expression.arms {
+            interpreter.check_bindings(&arm.pattern)?;
+        }
+
+        let error = match interpreter.evaluate(try_expression.body, optional_env.clone()) {
+            // Running into the limits of the interpreter is not something the program can recover from.
+            Err(error) if self::is_fatal(&error) => return Err(error),
+
+            Err(error) => error,
+            Ok(value)  => return Ok(value),
+        };
+
+        let value = Obj::new(ObjKind::Error(ErrorValue::from(&error)));
+        let given = Pattern::Value(ValuePattern { obj: Box::new(value) });
+
+        for arm in try_expression.arms {
+            if let Ok(bindings) = arm.pattern.linearize(given.clone()) {
+                let env = match &optional_env {
+                    Some(env) => env.extend(bindings),
+                    None      => bindings,
+                };
+
+                return interpreter.evaluate_in_scope(arm.body, env)
+            }
+        }
+
+        Err(error)
+    }
+}
+
+fn is_fatal(error: &InterpreterError) -> bool {
+    matches!(error.inner(), InterpreterError::TooMuchRecursion | InterpreterError::StepLimitExceeded)
+}
+
+fn expect_try(obj: Obj) -> Result<Try, InterpreterError> {
+    match obj.kind {
+        ObjKind::Try(try_expression) => Ok(try_expression),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("TryExpression"),
+            found: obj.get_type(),
+        }),
+    }
+}
@@ -15,18 +15,18 @@ pub struct ValueVisitor;
 impl Visitor for ValueVisitor {
     fn evaluate(
         &self,
-        interpreter: &mut Interpreter,
-        optional_env: Option<Environment>,
+        _interpreter: &mut Interpreter,
+        _optional_env: Option<Environment>,
         obj: Obj,
     ) -> InterpreterResult {
 
-        /// Values simply evaluate to themselves.
+        // Values simply evaluate to themselves.
         match &obj.kind {
-            Int(_) | UInt(_) | Float(_) | String(_) | Boolean(_) => Ok(Box::new(obj)),
+            Int(_) | UInt(_) | Float(_) | String(_) | Boolean(_) | Error(_) => Ok(Box::new(obj)),
 
             _ => Err(InterpreterError::UnexpectedType { 
                 found: obj.get_type(),
-                expected: "Int | UInt | String | Float | Boolean".to_string(),
+                expected: "Int | UInt | String | Float | Boolean | Error".to_string(),
             })
         }
     }
@@ -29,6 +29,12 @@ impl Block {
     }
 }
 
+impl Drop for Block {
+    fn drop(&mut self) {
+        internal::deallocate_block(self.ptr, self.size);
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum BlockError {
     /// Usually means requested block size, and therefore alignment, wasn't a
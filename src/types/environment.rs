@@ -1,36 +1,83 @@
 use crate::types::VariablePattern;
 use crate::types::Obj;
+use crate::interpreter::InterpreterError;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub entries: HashMap<VariablePattern, Box<Obj>>,
+    /// The variables which may be assigned a new value. All others are immutable.
+    pub mutable: HashSet<VariablePattern>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            mutable: HashSet::new(),
         }
     }
 
     pub fn empty() -> Self {
         Self {
             entries: HashMap::new(),
+            mutable: HashSet::new(),
         }
     }
 
     pub fn from(entries: HashMap<VariablePattern, Box<Obj>>) -> Self {
         Self {
             entries,
+            mutable: HashSet::new(),
         }
     }
 
     pub fn extend(&self, other: Self) -> Self {
         let mut entries = self.entries.clone();
-        entries.extend(other.entries);
+        entries.extend(other.entries.clone());
+
+        // A variable of the other environment replaces ours, including whether it is mutable.
+        let mutable = self.mutable
+            .iter()
+            .filter(|variable_pattern| !other.entries.contains_key(*variable_pattern))
+            .chain(other.mutable.iter())
+            .cloned()
+            .collect();
+
+        Self {
+            entries,
+            mutable,
+        }
+    }
+
+    pub fn is_mutable(&self, variable_pattern: &VariablePattern) -> bool {
+        self.mutable.contains(variable_pattern)
+    }
+
+    /// Combine the bindings of two environments created by matching parts of the same pattern.
+    ///
+    /// A name bound in both environments acts as an equality constraint, so the
+    /// environments are only combined if both values are equal.
+    pub fn merge(&self, other: Self) -> Result<Self, InterpreterError> {
+        let mut entries = self.entries.clone();
+
+        for (variable_pattern, obj) in other.entries {
+            if let Some(existing) = entries.get(&variable_pattern) {
+                if *existing != obj {
+                    return Err(InterpreterError::NoMatch)
+                }
+            } else {
+                entries.insert(variable_pattern, obj);
+            }
+        }
 
-        Self::from(entries)
+        Ok(Self::from(entries))
     }
 }
\ No newline at end of file
@@ -0,0 +1,149 @@
+//! Errors as values, which Mag programs can throw and catch.
+//!
+//! Programs create error values with the builtin `error(kind, message, payload)` constructor.
+//! [`Throw`] expressions raise them and [`Try`] expressions handle them.
+
+use crate::interpreter::InterpreterError;
+use crate::types::{
+    FieldPattern,
+    MatchArm,
+    Obj,
+    ObjKind,
+    PairPattern,
+    Pattern,
+    TuplePattern,
+    ValuePattern,
+};
+
+/// An error which can be handled by the program itself.
+///
+/// Runtime errors are turned into error values whose kind is the name of the error,
+/// like `DivisionByZero` or `NoMatchingReceiver`. When matched against a pattern, an
+/// error looks like the tuple `(kind: k, message: m, payload: p)`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ErrorValue {
+    pub kind:    String,
+    pub message: String,
+    /// Any value which gives more details about the error.
+    pub payload: Box<Obj>,
+}
+
+/// An expression which raises an error, like `throw value`.
+///
+/// Error values are thrown as they are. Any other value becomes the payload of
+/// an error with the kind `Error`, and strings also become its message.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Throw {
+    pub value: Box<Obj>,
+}
+
+/// An expression which handles errors raised by its body, like `try body catch { pattern => handler }`.
+///
+/// The arms are matched against the error value in order, and the first matching arm
+/// handles the error. If no arm matches, the error is raised again.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Try {
+    pub body: Box<Obj>,
+    pub arms: Vec<MatchArm>,
+}
+
+impl ErrorValue {
+    pub fn new(kind: String, message: String, payload: Box<Obj>) -> Self {
+        Self {
+            kind,
+            message,
+            payload,
+        }
+    }
+
+    /// Return the tuple of fields this error is matched as.
+    pub fn to_pattern(&self) -> Pattern {
+        let field = |name: &str, obj: Obj| Pattern::Field(FieldPattern {
+            name:    name.to_string(),
+            value:   Box::new(Pattern::Value(ValuePattern { obj: Box::new(obj) })),
+            default: None,
+        });
+
+        let fields = Pattern::Pair(PairPattern {
+            left:  Box::new(field("kind", Obj::new(ObjKind::String(self.kind.clone())))),
+            right: Box::new(Pattern::Pair(PairPattern {
+                left:  Box::new(field("message", Obj::new(ObjKind::String(self.message.clone())))),
+                right: Box::new(field("payload", *self.payload.clone())),
+            })),
+        });
+
+        Pattern::Tuple(TuplePattern { child: Box::new(fields) })
+    }
+}
+
+impl From<&InterpreterError> for ErrorValue {
+    fn from(error: &InterpreterError) -> Self {
+        match error.inner() {
+            InterpreterError::Thrown { error } => error.clone(),
+
+            inner => ErrorValue::new(
+                self::kind_of(inner).to_string(),
+                inner.to_string(),
+                Box::new(Obj::new(ObjKind::Nothing)),
+            ),
+        }
+    }
+}
+
+impl Throw {
+    pub fn new(value: Box<Obj>) -> Self {
+        Self {
+            value,
+        }
+    }
+}
+
+impl Try {
+    pub fn new(body: Box<Obj>, arms: Vec<MatchArm>) -> Self {
+        Self {
+            body,
+            arms,
+        }
+    }
+}
+
+/// Return the name of an error, which is the kind of the error value it turns into.
+fn kind_of(error: &InterpreterError) -> &'static str {
+    match error {
+        InterpreterError::Unimplemented                => "Unimplemented",
+        InterpreterError::UnexpectedType { .. }        => "UnexpectedType",
+        InterpreterError::MethodAlreadyExists          => "MethodAlreadyExists",
+        InterpreterError::SignatureAlreadyExists       => "SignatureAlreadyExists",
+        InterpreterError::VariableAlreadyExists { .. } => "VariableAlreadyExists",
+        InterpreterError::NoMatchingReceiver           => "NoMatchingReceiver",
+        InterpreterError::NoMatchingMultimethod        => "NoMatchingMultimethod",
+        InterpreterError::NoMatchingVariable { .. }    => "NoMatchingVariable",
+        InterpreterError::AssignToUndefined { .. }     => "AssignToUndefined",
+        InterpreterError::AssignToImmutable { .. }     => "AssignToImmutable",
+        InterpreterError::NoMatchingVisitor            => "NoMatchingVisitor",
+        InterpreterError::NoMatchingArm { .. }         => "NoMatchingArm",
+        InterpreterError::NoMatch                      => "NoMatch",
+        InterpreterError::DuplicateField { .. }        => "DuplicateField",
+        InterpreterError::UnknownField { .. }          => "UnknownField",
+        InterpreterError::MissingField { .. }          => "MissingField",
+        InterpreterError::PositionalAfterField         => "PositionalAfterField",
+        InterpreterError::DuplicateBinding { .. }      => "DuplicateBinding",
+        InterpreterError::InconsistentBindings { .. }  => "InconsistentBindings",
+        InterpreterError::NoMatchingModule { .. }      => "NoMatchingModule",
+        InterpreterError::ModuleLoadFailed { .. }      => "ModuleLoadFailed",
+        InterpreterError::ParseFailed { .. }           => "ParseFailed",
+        InterpreterError::ImportCycle { .. }           => "ImportCycle",
+        InterpreterError::NoMatchingExport { .. }      => "NoMatchingExport",
+        InterpreterError::ConflictingImport { .. }     => "ConflictingImport",
+        InterpreterError::InvalidLiteral { .. }        => "InvalidLiteral",
+        InterpreterError::DivisionByZero               => "DivisionByZero",
+        InterpreterError::IntegerOverflow              => "IntegerOverflow",
+        InterpreterError::TooMuchRecursion             => "TooMuchRecursion",
+        InterpreterError::TooDeeplyNested              => "TooDeeplyNested",
+        InterpreterError::StepLimitExceeded            => "StepLimitExceeded",
+        InterpreterError::Thrown { .. }                => "Thrown",
+
+        InterpreterError::Located { error, .. }        => self::kind_of(error),
+        InterpreterError::Traced { error, .. }         => self::kind_of(error),
+    }
+}
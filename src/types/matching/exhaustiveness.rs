@@ -0,0 +1,378 @@
+//! A static checker for the arms of a `match` expression.
+//!
+//! Values are not typed ahead of time, so the checker infers the kind of value being matched
+//! from the arms themselves. Booleans and list shapes are checked for full coverage,
+//! while any other kind of value needs a catch-all arm.
+//!
+//! There are no enum types at runtime, so there are no variants to check for coverage.
+//! Values of a named type are treated like any other value and need a catch-all arm.
+
+use std::collections::BTreeSet;
+
+use crate::types::{
+    ObjKind,
+    Pattern,
+    ValuePattern,
+    VariablePattern,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MatchWarning {
+    /// The arm at the given index can never match because earlier arms match everything it does.
+    UnreachableArm { index: usize },
+    /// Values of the given shapes are not matched by any arm.
+    NonExhaustive { missing: Vec<String> },
+}
+
+impl std::fmt::Display for MatchWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchWarning::UnreachableArm { index }   => write!(f, "arm {} of a match expression can never be reached", index + 1),
+            MatchWarning::NonExhaustive { missing } => write!(f, "match expression does not cover {}", missing.join(", ")),
+        }
+    }
+}
+
+/// The values matched by the arms checked so far.
+#[derive(Debug, Default)]
+struct Coverage {
+    catch_all: bool,
+    booleans:  BTreeSet<bool>,
+    /// Lengths of lists whose items are all matched unconditionally.
+    lengths:   BTreeSet<usize>,
+    /// The smallest length from which on all lists are matched, if any.
+    rest_from: Option<usize>,
+    seen:      Vec<Pattern>,
+}
+
+/// Check the patterns of a `match` expression's arms, in order.
+pub fn check(patterns: &[Pattern]) -> Vec<MatchWarning> {
+    let mut warnings = vec![];
+    let mut coverage = Coverage::default();
+
+    for (index, pattern) in patterns.iter().enumerate() {
+        if coverage.covers(pattern) {
+            warnings.push(MatchWarning::UnreachableArm { index });
+        }
+
+        coverage.add(pattern);
+    }
+
+    let missing = coverage.missing(patterns);
+
+    if !missing.is_empty() {
+        warnings.push(MatchWarning::NonExhaustive { missing });
+    }
+
+    warnings
+}
+
+impl Coverage {
+    /// Return whether all values matched by the given pattern are already covered.
+    fn covers(&self, pattern: &Pattern) -> bool {
+        if self.catch_all || self.seen.contains(pattern) {
+            return true
+        }
+
+        if self::is_irrefutable(pattern) && self.is_complete() {
+            return true
+        }
+
+        match pattern {
+            Pattern::Alternative(alternative) => alternative.branches().iter().all(|branch| self.covers(branch)),
+            Pattern::Named(named)             => self.covers(&named.pattern),
+
+            _ => {
+                if let Some(boolean) = self::boolean_literal(pattern) {
+                    return self.booleans.contains(&boolean)
+                }
+
+                if self::is_typed(pattern, "Boolean") {
+                    return self.booleans.len() == 2
+                }
+
+                if self::is_typed(pattern, "List") {
+                    return self.covers_lists_from(0)
+                }
+
+                match self::list_shape(pattern) {
+                    Some(ListShape::Fixed(length))   => self.lengths.contains(&length) || self.covers_lists_from(length),
+                    Some(ListShape::AtLeast(length)) => self.covers_lists_from(length),
+                    None                             => false,
+                }
+            },
+        }
+    }
+
+    /// Return whether the arms so far match every value of the kind they all agree on,
+    /// like `true` and `false`, in which case nothing is left for a catch-all arm.
+    fn is_complete(&self) -> bool {
+        let mut kinds = vec![];
+        self.seen.iter().for_each(|pattern| self::collect_kinds(pattern, &mut kinds));
+
+        match kinds.first() {
+            Some(Kind::Boolean) => kinds.iter().all(|kind| *kind == Kind::Boolean) && self.booleans.len() == 2,
+            Some(Kind::List)    => kinds.iter().all(|kind| *kind == Kind::List) && self.covers_lists_from(0),
+
+            _ => false,
+        }
+    }
+
+    /// Return whether every list with at least the given length is covered.
+    fn covers_lists_from(&self, length: usize) -> bool {
+        match self.rest_from {
+            Some(rest_from) => (length..rest_from).all(|n| self.lengths.contains(&n)),
+            None            => false,
+        }
+    }
+
+    fn add(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Alternative(alternative) => {
+                for branch in alternative.branches() {
+                    self.add(branch);
+                }
+            },
+
+            Pattern::Named(named) => self.add(&named.pattern),
+
+            _ => {
+                if self::is_irrefutable(pattern) {
+                    self.catch_all = true;
+                } else if let Some(boolean) = self::boolean_literal(pattern) {
+                    self.booleans.insert(boolean);
+                } else if self::is_typed(pattern, "Boolean") {
+                    self.booleans.extend([true, false]);
+                } else if self::is_typed(pattern, "List") {
+                    self.rest_from = Some(0);
+                } else {
+                    match self::list_shape(pattern) {
+                        Some(ListShape::Fixed(length)) => {
+                            self.lengths.insert(length);
+                        },
+
+                        Some(ListShape::AtLeast(length)) => {
+                            self.rest_from = Some(self.rest_from.map_or(length, |rest_from| rest_from.min(length)));
+                        },
+
+                        None => (),
+                    }
+                }
+            },
+        }
+
+        self.seen.push(pattern.clone());
+    }
+
+    /// Describe the values which are not covered, based on the kinds of values the arms match.
+    fn missing(&self, patterns: &[Pattern]) -> Vec<String> {
+        if self.catch_all {
+            return vec![]
+        }
+
+        let mut kinds = vec![];
+        patterns.iter().for_each(|pattern| self::collect_kinds(pattern, &mut kinds));
+
+        if !kinds.is_empty() && kinds.iter().all(|kind| *kind == Kind::Boolean) {
+            return [true, false]
+                .iter()
+                .copied()
+                .filter(|boolean| !self.booleans.contains(boolean))
+                .map(|boolean| boolean.to_string())
+                .collect()
+        }
+
+        if !kinds.is_empty() && kinds.iter().all(|kind| *kind == Kind::List) {
+            return match self.rest_from {
+                Some(rest_from) => (0..rest_from)
+                    .filter(|length| !self.lengths.contains(length))
+                    .map(|length| self::describe_list(length, false))
+                    .collect(),
+
+                None => {
+                    let length = self.lengths.iter().max().map_or(0, |max| max + 1);
+
+                    (0..length)
+                        .filter(|length| !self.lengths.contains(length))
+                        .map(|length| self::describe_list(length, false))
+                        .chain(std::iter::once(self::describe_list(length, true)))
+                        .collect()
+                },
+            }
+        }
+
+        vec![String::from("_")]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Kind {
+    Boolean,
+    List,
+    Other,
+}
+
+fn collect_kinds(pattern: &Pattern, kinds: &mut Vec<Kind>) {
+    match pattern {
+        Pattern::Alternative(alternative) => {
+            alternative.branches().iter().for_each(|branch| self::collect_kinds(branch, kinds));
+        },
+
+        Pattern::Named(named) => self::collect_kinds(&named.pattern, kinds),
+
+        _ => {
+            if self::boolean_literal(pattern).is_some() || self::is_typed(pattern, "Boolean") {
+                kinds.push(Kind::Boolean);
+            } else if let Pattern::List(_) = pattern {
+                kinds.push(Kind::List);
+            } else if self::is_typed(pattern, "List") {
+                kinds.push(Kind::List);
+            } else {
+                kinds.push(Kind::Other);
+            }
+        },
+    }
+}
+
+enum ListShape {
+    /// A list pattern with the given number of items, which all match unconditionally.
+    Fixed(usize),
+    /// A list pattern with the given number of unconditional items followed by a rest pattern.
+    AtLeast(usize),
+}
+
+fn list_shape(pattern: &Pattern) -> Option<ListShape> {
+    if let Pattern::List(list) = pattern {
+        let items = list.items();
+
+        match items.split_last() {
+            Some((Pattern::Rest(_), init)) => {
+                if init.iter().all(self::is_irrefutable) {
+                    return Some(ListShape::AtLeast(init.len()))
+                }
+            },
+
+            _ => {
+                if items.iter().all(self::is_irrefutable) {
+                    return Some(ListShape::Fixed(items.len()))
+                }
+            },
+        }
+    }
+
+    None
+}
+
+/// Return whether a pattern matches any value at all.
+fn is_irrefutable(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Variable(VariablePattern { type_id: None, .. }) => true,
+        Pattern::Named(named)                                    => self::is_irrefutable(&named.pattern),
+        Pattern::Alternative(alternative)                        => alternative.branches().iter().any(self::is_irrefutable),
+
+        _ => false,
+    }
+}
+
+fn is_typed(pattern: &Pattern, type_id: &str) -> bool {
+    match pattern {
+        Pattern::Variable(VariablePattern { type_id: Some(found), .. }) => found == type_id,
+
+        _ => false,
+    }
+}
+
+fn boolean_literal(pattern: &Pattern) -> Option<bool> {
+    match pattern {
+        Pattern::Value(ValuePattern { obj }) => {
+            if let ObjKind::Boolean(boolean) = &obj.kind {
+                Some(*boolean)
+            } else {
+                None
+            }
+        },
+
+        _ => None,
+    }
+}
+
+fn describe_list(length: usize, rest: bool) -> String {
+    let mut items = vec![String::from("_"); length];
+
+    if rest {
+        items.push(String::from("..._"));
+    }
+
+    format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Obj, PairPattern, RestPattern};
+
+    fn boolean(value: bool) -> Pattern {
+        Pattern::Value(ValuePattern { obj: Box::new(Obj::new(ObjKind::Boolean(value))) })
+    }
+
+    fn wildcard() -> Pattern {
+        Pattern::Variable(VariablePattern { name: None, type_id: None })
+    }
+
+    fn list(items: Vec<Pattern>) -> Pattern {
+        let child = items.into_iter().rev().reduce(|right, left| Pattern::Pair(PairPattern {
+            left:  Box::new(left),
+            right: Box::new(right),
+        }));
+
+        Pattern::List(crate::types::ListPattern { child: child.map(Box::new) })
+    }
+
+    #[test]
+    fn booleans_are_covered_by_both_values() {
+        assert_eq!(self::check(&[boolean(true), boolean(false)]), vec![]);
+
+        assert_eq!(
+            self::check(&[boolean(true)]),
+            vec![MatchWarning::NonExhaustive { missing: vec![String::from("false")] }],
+        );
+    }
+
+    #[test]
+    fn arms_after_a_catch_all_are_unreachable() {
+        assert_eq!(
+            self::check(&[wildcard(), boolean(true)]),
+            vec![MatchWarning::UnreachableArm { index: 1 }],
+        );
+    }
+
+    #[test]
+    fn lists_are_covered_by_empty_and_head_tail_patterns() {
+        let rest = Pattern::Rest(RestPattern { name: None });
+
+        assert_eq!(self::check(&[list(vec![]), list(vec![wildcard(), rest.clone()])]), vec![]);
+
+        assert_eq!(
+            self::check(&[list(vec![wildcard(), rest])]),
+            vec![MatchWarning::NonExhaustive { missing: vec![String::from("[]")] }],
+        );
+    }
+
+    #[test]
+    fn catch_all_arms_after_complete_sets_are_unreachable() {
+        assert_eq!(
+            self::check(&[boolean(true), boolean(false), wildcard()]),
+            vec![MatchWarning::UnreachableArm { index: 2 }],
+        );
+
+        let rest = Pattern::Rest(RestPattern { name: None });
+
+        assert_eq!(
+            self::check(&[list(vec![]), list(vec![wildcard(), rest]), wildcard()]),
+            vec![MatchWarning::UnreachableArm { index: 2 }],
+        );
+
+        // A single boolean doesn't tell the value apart from other kinds.
+        assert_eq!(self::check(&[boolean(true), wildcard()]), vec![]);
+    }
+}
@@ -0,0 +1,102 @@
+//! Explicit pattern matching with `match` expressions.
+
+pub mod exhaustiveness;
+
+pub use self::exhaustiveness::MatchWarning;
+
+use crate::types::{Obj, ObjKind, Pattern};
+
+/// An expression which matches a value against a list of arms, like `match value { pattern => expr }`.
+///
+/// The arms are tried in order and the body of the first one whose pattern matches
+/// is evaluated with the variables bound by that pattern.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Match {
+    pub value: Box<Obj>,
+    pub arms:  Vec<MatchArm>,
+}
+
+/// A single arm of a `match` expression.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body:    Box<Obj>,
+}
+
+impl Match {
+    pub fn new(value: Box<Obj>, arms: Vec<MatchArm>) -> Self {
+        Self {
+            value,
+            arms,
+        }
+    }
+
+    /// Look for arms which can never be reached and for values which are not covered by any arm.
+    pub fn check(&self) -> Vec<MatchWarning> {
+        let patterns: Vec<Pattern> = self.arms
+            .iter()
+            .map(|arm| arm.pattern.clone())
+            .collect();
+
+        self::exhaustiveness::check(&patterns)
+    }
+}
+
+/// Check every `match` expression inside of an object, including the ones in arms
+/// and method bodies which are never evaluated, and return their warnings in order.
+pub fn check_all(obj: &Obj) -> Vec<MatchWarning> {
+    let mut warnings = vec![];
+    self::collect_warnings(obj, &mut warnings);
+
+    warnings
+}
+
+fn collect_warnings(obj: &Obj, warnings: &mut Vec<MatchWarning>) {
+    match &obj.kind {
+        ObjKind::Match(match_expression) => {
+            warnings.extend(match_expression.check());
+            self::collect_warnings(&match_expression.value, warnings);
+
+            for arm in &match_expression.arms {
+                self::collect_warnings(&arm.body, warnings);
+            }
+        },
+
+        ObjKind::Multimethod(multimethod) => {
+            for receiver in &multimethod.receivers {
+                if let Some(guard) = &receiver.guard {
+                    self::collect_warnings(guard, warnings);
+                }
+
+                self::collect_warnings(&receiver.body, warnings);
+            }
+        },
+
+        ObjKind::Try(try_expression) => {
+            self::collect_warnings(&try_expression.body, warnings);
+
+            for arm in &try_expression.arms {
+                self::collect_warnings(&arm.body, warnings);
+            }
+        },
+
+        ObjKind::Definition(definition) => self::collect_warnings(&definition.value, warnings),
+        ObjKind::Assignment(assignment) => self::collect_warnings(&assignment.value, warnings),
+        ObjKind::Throw(throw)           => self::collect_warnings(&throw.value, warnings),
+
+        ObjKind::List(_) => {
+            for item in obj.list_items().unwrap_or_default() {
+                self::collect_warnings(&item, warnings);
+            }
+        },
+
+        ObjKind::Sequence(sequence) => {
+            for expression in &sequence.expressions {
+                self::collect_warnings(expression, warnings);
+            }
+        },
+
+        // Expressions from the parser have no `match` syntax, and the rest are values.
+        _ => {},
+    }
+}
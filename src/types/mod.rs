@@ -1,9 +1,31 @@
+//! The values and expressions the interpreter works with.
+//!
+//! Some of them have no syntax in `magc` yet, so code parsed from source never contains
+//! them. They are built by embedders which construct programs directly, and by tests:
+//!
+//! - default values of field patterns, like `retries: n Int = 3`
+//! - rest patterns, like `...rest`, and so the rest item of list patterns like `[head, ...tail]`
+//! - named patterns, like `p @ (x, y)`
+//! - range patterns, like `0..10`
+//! - `let` definitions, assignments and sequences of expressions
+//! - `throw` and `try` expressions
+//!
+//! Each of them is printed in the syntax it is meant to have once the parser supports it.
+
 pub mod environment;
+pub mod error;
+pub mod matching;
+pub mod module;
 pub mod obj;
 pub mod multimethod;
 pub mod pattern;
+pub mod scope;
 
 pub use self::environment::Environment;
+pub use self::error::{ErrorValue, Throw, Try};
+pub use self::matching::{Match, MatchArm, MatchWarning};
+pub use self::module::{Import, ImportKind, Module};
 pub use self::multimethod::*;
-pub use self::obj::{Obj, ObjKind};
-pub use self::pattern::*;
\ No newline at end of file
+pub use self::obj::{Obj, ObjKind, Source};
+pub use self::pattern::*;
+pub use self::scope::{Assignment, Definition, Sequence};
\ No newline at end of file
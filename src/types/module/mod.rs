@@ -0,0 +1,65 @@
+//! Modules, which give every source file a namespace of its own.
+
+use std::collections::BTreeSet;
+
+/// A module which has been evaluated, along with the names it makes available to importers.
+///
+/// The variables of a module live in its namespace inside of the interpreter. Every
+/// top-level variable is exported, except for those whose name starts with an underscore.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Module {
+    /// The name the module was first imported by.
+    pub name:    String,
+    /// The path which identifies the module and its namespace, as returned by the module loader.
+    pub path:    String,
+    pub exports: BTreeSet<String>,
+}
+
+/// An expression which makes the exports of another module available, like `import math`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Import {
+    /// The name of the module to import.
+    pub module: String,
+    pub kind:   ImportKind,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ImportKind {
+    /// Bind the module itself to a name, so its exports are reached like `math.sqrt`.
+    ///
+    /// Without an alias, the last part of the module name is used, so `import net.http` binds `http`.
+    Qualified { alias: Option<String> },
+    /// Define the given exports directly in the current scope, like `import math (sqrt, pow)`.
+    Selective { names: Vec<String> },
+}
+
+impl Module {
+    pub fn new(name: String, path: String, exports: BTreeSet<String>) -> Self {
+        Self {
+            name,
+            path,
+            exports,
+        }
+    }
+
+    /// Return whether a top-level variable with the given name is visible to importers.
+    pub fn is_exported_name(name: &str) -> bool {
+        !name.starts_with('_')
+    }
+}
+
+impl Import {
+    pub fn qualified(module: String, alias: Option<String>) -> Self {
+        Self {
+            module,
+            kind: ImportKind::Qualified { alias },
+        }
+    }
+
+    pub fn selective(module: String, names: Vec<String>) -> Self {
+        Self {
+            module,
+            kind: ImportKind::Selective { names },
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use magc::type_system::Typed;
+
+use crate::types::{
+    Pattern,
+    VariablePattern,
+};
+
+/// The runtime shape and types of an evaluated argument pattern.
+///
+/// Two calls with the same key are able to reach the same set of receivers, so the key
+/// is used to look up previously computed candidates in a [`DispatchCache`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum DispatchKey {
+    Field { name: String, value: Box<DispatchKey> },
+    List,
+    Pair(Box<DispatchKey>, Box<DispatchKey>),
+    Tuple(Box<DispatchKey>),
+    /// A value of the given runtime type.
+    Value(Option<String>),
+    /// A variable which has not been resolved to a value.
+    Variable,
+}
+
+impl From<&Pattern> for DispatchKey {
+    fn from(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::Field(field) => Self::Field {
+                name:  field.name.clone(),
+                value: Box::new(Self::from(&*field.value)),
+            },
+
+            Pattern::List(_)          => Self::List,
+            Pattern::Named(named)     => Self::from(&*named.pattern),
+
+            // Alternatives and ranges only appear in signatures and are never part of a call's arguments.
+            Pattern::Alternative(_)   => Self::Variable,
+            Pattern::Range(_)         => Self::Variable,
+
+            Pattern::Pair(pair)       => Self::Pair(
+                Box::new(Self::from(&*pair.left)),
+                Box::new(Self::from(&*pair.right)),
+            ),
+
+            Pattern::Tuple(tuple)     => Self::Tuple(Box::new(Self::from(&*tuple.child))),
+            Pattern::Value(value)     => Self::Value(value.obj.get_type()),
+            Pattern::Variable(_)      => Self::Variable,
+
+            // Rest patterns only appear in signatures and are never part of a call's arguments.
+            Pattern::Rest(_)          => Self::Variable,
+        }
+    }
+}
+
+/// How many argument shapes a [`DispatchCache`] remembers before it starts over.
+///
+/// Records with ever new field names give ever new keys, so a cache which kept
+/// every one of them would grow without bound.
+pub const MAX_ENTRIES: usize = 256;
+
+/// Maps the argument shape of a call to the receivers which may be able to handle it.
+///
+/// Every leaf of a multimethod's [`DecisionTree`](super::DecisionTree) has its own cache. Multimethods
+/// are cloned out of the environment on every call, so the entries are shared between all clones.
+///
+/// The entries are behind a mutex rather than a `RefCell` so values holding a multimethod stay `Send`
+/// and an interpreter's results can be handed to another thread.
+#[derive(Debug, Clone, Default)]
+pub struct DispatchCache {
+    entries: Arc<Mutex<HashMap<Option<DispatchKey>, Vec<usize>>>>,
+}
+
+impl DispatchCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the indices of the candidate receivers for the given key, if they are known.
+    pub fn get(&self, key: &Option<DispatchKey>) -> Option<Vec<usize>> {
+        self.lock().get(key).cloned()
+    }
+
+    /// Remember the candidates for a key. A full cache is cleared first, since the candidates can always be computed again.
+    pub fn insert(&self, key: Option<DispatchKey>, candidates: Vec<usize>) {
+        let mut entries = self.lock();
+
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            entries.clear();
+        }
+
+        entries.insert(key, candidates);
+    }
+
+    // Entries are only ever inserted whole, so a panic while the lock was held can't leave a broken one behind.
+    fn lock(&self) -> MutexGuard<'_, HashMap<Option<DispatchKey>, Vec<usize>>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// The cache does not affect the behavior of a multimethod, so it is ignored when comparing.
+impl PartialEq for DispatchCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for DispatchCache {}
+
+/// Check whether a receiver's pattern could match an argument of the given shape.
+///
+/// This is a conservative approximation of [`Pattern::linearize`] which only looks at shapes
+/// and types, so a pattern which accepts a key may still fail to match the actual values.
+pub fn accepts(pattern: &Pattern, key: &DispatchKey) -> bool {
+    match (pattern, key) {
+        (Pattern::Field(field), DispatchKey::Field { name, value }) => {
+            field.name == *name && self::accepts(&field.value, value)
+        },
+
+        (Pattern::Pair(_), key) => self::accepts_sequence(&pattern.clone().flatten(), &self::flatten(key)),
+        (Pattern::Rest(_), _)   => true,
+
+        (Pattern::Named(named), key) => self::accepts(&named.pattern, key),
+
+        (Pattern::Alternative(alternative), key) => {
+            alternative.branches().iter().any(|branch| self::accepts(branch, key))
+        },
+
+        (Pattern::List(_), DispatchKey::List)           => true,
+        (Pattern::List(_), DispatchKey::Value(type_id)) => *type_id == Some(String::from("List")),
+
+        (Pattern::Tuple(tuple), DispatchKey::Tuple(child)) => self::accepts(&tuple.child, child),
+
+        // Tuples and records stored in variables are destructured when they are matched.
+        (Pattern::Tuple(_), DispatchKey::Value(_)) => true,
+        (Pattern::Field(_), DispatchKey::Value(_)) => true,
+
+        (Pattern::Value(value), DispatchKey::Value(type_id)) => value.obj.get_type() == *type_id,
+
+        (Pattern::Range(_), DispatchKey::Value(Some(type_id))) => {
+            ["Int", "UInt", "Float", "String"].contains(&type_id.as_str())
+        },
+
+        // A nameless variable does not destructure anything, so it matches any shape.
+        (Pattern::Variable(VariablePattern { name: None, .. }), _) => true,
+
+        (Pattern::Variable(variable), DispatchKey::Value(type_id)) => {
+            variable.type_id.is_none() || variable.type_id == *type_id
+        },
+
+        // Named variables capture structured values as a whole, but not a list of arguments.
+        (Pattern::Variable(_), DispatchKey::Pair(..)) => false,
+        (Pattern::Variable(_), _)                     => true,
+
+        _ => false,
+    }
+}
+
+/// Check the items of a flattened pair chain in the same way as the pattern matcher does.
+fn accepts_sequence(reference: &[Pattern], given: &[&DispatchKey]) -> bool {
+    let (reference_positional, reference_fields): (Vec<&Pattern>, Vec<&Pattern>) = reference
+        .iter()
+        .partition(|item| !matches!(item, Pattern::Field(_)));
+
+    let (given_positional, given_fields): (Vec<&DispatchKey>, Vec<&DispatchKey>) = given
+        .iter()
+        .copied()
+        .partition(|item| !matches!(item, DispatchKey::Field { .. }));
+
+    self::accepts_positional(&reference_positional, &given_positional)
+        && self::accepts_fields(&reference_fields, &given_fields)
+}
+
+fn accepts_positional(reference: &[&Pattern], given: &[&DispatchKey]) -> bool {
+    match reference.split_first() {
+        None                         => given.is_empty(),
+        Some((Pattern::Rest(_), _))  => true,
+
+        Some((head, tail)) => {
+            let matches_first = match given.split_first() {
+                Some((first, others)) => self::accepts(head, first) && self::accepts_positional(tail, others),
+                None                  => false,
+            };
+
+            // Optional items may be left out of the call entirely.
+            matches_first || (head.is_optional() && self::accepts_positional(tail, given))
+        },
+    }
+}
+
+/// Fields are matched by name, so their order does not matter.
+fn accepts_fields(reference: &[&Pattern], given: &[&DispatchKey]) -> bool {
+    let all_given_known = given.iter().all(|key| {
+        reference.iter().any(|pattern| self::accepts(pattern, key))
+    });
+
+    let all_reference_given = reference.iter().all(|pattern| {
+        pattern.is_optional() || given.iter().any(|key| self::accepts(pattern, key))
+    });
+
+    all_given_known && all_reference_given
+}
+
+fn flatten(key: &DispatchKey) -> Vec<&DispatchKey> {
+    match key {
+        DispatchKey::Pair(left, right) => {
+            let mut items = self::flatten(left);
+            items.extend(self::flatten(right));
+
+            items
+        },
+
+        key => vec![key],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(n: usize) -> Option<DispatchKey> {
+        Some(DispatchKey::Field { name: format!("f{}", n), value: Box::new(DispatchKey::Variable) })
+    }
+
+    #[test]
+    fn full_caches_start_over() {
+        let cache = DispatchCache::new();
+
+        for n in 0..MAX_ENTRIES {
+            cache.insert(key(n), vec![n]);
+        }
+
+        assert_eq!(cache.lock().len(), MAX_ENTRIES);
+
+        // Keys which are already known don't take up more room.
+        cache.insert(key(0), vec![0]);
+        assert_eq!(cache.lock().len(), MAX_ENTRIES);
+
+        cache.insert(key(MAX_ENTRIES), vec![MAX_ENTRIES]);
+
+        assert_eq!(cache.lock().len(), 1);
+        assert_eq!(cache.get(&key(0)), None);
+        assert_eq!(cache.get(&key(MAX_ENTRIES)), Some(vec![MAX_ENTRIES]));
+    }
+}
@@ -0,0 +1,428 @@
+//! Receiver selection using a decision tree over the positional arguments of a call.
+//!
+//! The tree only rules out receivers which can't possibly match, so the remaining
+//! candidates are still checked with [`Pattern::linearize`] in order of precedence.
+//! This keeps the results identical to testing every receiver one by one.
+
+use std::collections::HashMap;
+
+use magc::type_system::Typed;
+
+use crate::types::{
+    Obj,
+    ObjKind,
+    Pattern,
+    ValuePattern,
+    VariablePattern,
+};
+
+use super::cache::{self, DispatchCache, DispatchKey};
+use super::Receiver;
+
+/// The maximum number of arguments the tree switches on before falling back to a list of candidates.
+const MAX_DEPTH: usize = 4;
+
+/// A value which can be used as a hash map key.
+///
+/// Two literals are equal exactly when value patterns would consider them equal.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum Literal {
+    Int(i64),
+    UInt(u64),
+    Float(String),
+    Boolean(bool),
+    String(String),
+}
+
+impl Literal {
+    fn from_obj(obj: &Obj) -> Option<Self> {
+        match &obj.kind {
+            ObjKind::Int(n)       => Some(Self::Int(*n)),
+            ObjKind::UInt(n)      => Some(Self::UInt(*n)),
+            ObjKind::Float(n)     => Some(Self::Float(n.clone())),
+            ObjKind::Boolean(b)   => Some(Self::Boolean(*b)),
+            ObjKind::String(s)    => Some(Self::String(s.clone())),
+
+            _ => None,
+        }
+    }
+}
+
+/// What a receiver requires of the argument at a certain position.
+enum Discriminant {
+    /// An exact value, along with the value's type.
+    Literal(Literal, Option<String>),
+    /// Any value of the given type.
+    Type(String),
+    /// Anything, as far as the tree is concerned.
+    Any,
+}
+
+#[derive(Debug)]
+enum Node {
+    /// The remaining candidates, with a cache of the ones which accept a given argument shape.
+    Leaf {
+        receivers: Vec<usize>,
+        cache:     DispatchCache,
+    },
+
+    Switch {
+        column:   usize,
+        /// Receivers for arguments equal to a literal value required by some receiver.
+        literals: HashMap<Literal, Node>,
+        /// Receivers for arguments of a type required by some receiver, if no literal matched.
+        types:    HashMap<String, Node>,
+        /// Receivers for any other value.
+        default:  Box<Node>,
+        /// Receivers for arguments which are not a plain value or are missing.
+        all:      Box<Node>,
+    },
+}
+
+/// The receivers of a multimethod compiled into a switch over argument values and types.
+#[derive(Debug)]
+pub struct DecisionTree {
+    root:    Node,
+    /// Receivers which are able to handle a call without any arguments.
+    nullary: Vec<usize>,
+}
+
+impl DecisionTree {
+    pub fn new(receivers: &[Receiver]) -> Self {
+        // The sort is stable, so receivers with the same precedence keep their definition order.
+        let mut order: Vec<usize> = (0..receivers.len()).collect();
+        order.sort_by(|a, b| receivers[*b].get_precedence().cmp(&receivers[*a].get_precedence()));
+
+        let nullary = order
+            .iter()
+            .copied()
+            .filter(|index| {
+                match &receivers[*index].signature {
+                    Some(pattern) => pattern.is_optional(),
+                    None          => true,
+                }
+            })
+            .collect();
+
+        let columns = receivers
+            .iter()
+            .map(|receiver| receiver.signature.clone().map_or(0, |pattern| self::positional_items(pattern).len()))
+            .max()
+            .unwrap_or(0)
+            .min(MAX_DEPTH);
+
+        Self {
+            root: self::build(receivers, order, 0, columns),
+            nullary,
+        }
+    }
+
+    /// Return the indices of all receivers which may match the given arguments, sorted by precedence.
+    pub fn candidates(&self, receivers: &[Receiver], given: &Option<Pattern>) -> Vec<usize> {
+        let pattern = match given {
+            Some(pattern) => pattern,
+            None          => return self.nullary.clone(),
+        };
+
+        let items = self::positional_items(pattern.clone());
+        let mut node = &self.root;
+
+        loop {
+            match node {
+                Node::Leaf { receivers: indices, cache } => {
+                    let key = Some(DispatchKey::from(pattern));
+
+                    if let Some(candidates) = cache.get(&key) {
+                        return candidates
+                    }
+
+                    let candidates: Vec<usize> = indices
+                        .iter()
+                        .copied()
+                        .filter(|index| {
+                            match (&receivers[*index].signature, &key) {
+                                (Some(signature), Some(key)) => cache::accepts(signature, key),
+
+                                _ => false,
+                            }
+                        })
+                        .collect();
+
+                    cache.insert(key, candidates.clone());
+
+                    return candidates
+                },
+
+                Node::Switch { column, literals, types, default, all } => {
+                    node = match items.get(*column) {
+                        Some(Pattern::Value(ValuePattern { obj })) => {
+                            let by_literal = Literal::from_obj(obj).and_then(|literal| literals.get(&literal));
+                            let by_type    = obj.get_type().and_then(|type_id| types.get(&type_id));
+
+                            by_literal.or(by_type).unwrap_or(default.as_ref())
+                        },
+
+                        _ => all.as_ref(),
+                    };
+                },
+            }
+        }
+    }
+}
+
+// The tree is derived from the receivers, so it is ignored when comparing multimethods.
+impl PartialEq for DecisionTree {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for DecisionTree {}
+
+fn build(receivers: &[Receiver], indices: Vec<usize>, column: usize, columns: usize) -> Node {
+    if indices.len() <= 1 || column >= columns {
+        return Node::Leaf {
+            receivers: indices,
+            cache:     DispatchCache::new(),
+        }
+    }
+
+    let discriminants: Vec<(usize, Discriminant)> = indices
+        .iter()
+        .map(|index| (*index, self::discriminant(&receivers[*index], column)))
+        .collect();
+
+    let mut literal_keys: Vec<(Literal, Option<String>)> = vec![];
+    let mut type_keys:    Vec<String>                    = vec![];
+
+    for (_, discriminant) in &discriminants {
+        match discriminant {
+            Discriminant::Literal(literal, type_id) => {
+                if !literal_keys.iter().any(|(known, _)| known == literal) {
+                    literal_keys.push((literal.clone(), type_id.clone()));
+                }
+            },
+
+            Discriminant::Type(type_id) => {
+                if !type_keys.contains(type_id) {
+                    type_keys.push(type_id.clone());
+                }
+            },
+
+            Discriminant::Any => (),
+        }
+    }
+
+    // Nothing to switch on at this position, so move on to the next one.
+    if literal_keys.is_empty() && type_keys.is_empty() {
+        return self::build(receivers, indices, column + 1, columns)
+    }
+
+    let select = |keep: &dyn Fn(&Discriminant) -> bool| -> Vec<usize> {
+        discriminants
+            .iter()
+            .filter(|(_, discriminant)| keep(discriminant))
+            .map(|(index, _)| *index)
+            .collect()
+    };
+
+    let literals = literal_keys
+        .iter()
+        .map(|(literal, literal_type)| {
+            let bucket = select(&|discriminant| match discriminant {
+                Discriminant::Literal(other, _) => other == literal,
+                Discriminant::Type(type_id)     => Some(type_id) == literal_type.as_ref(),
+                Discriminant::Any               => true,
+            });
+
+            (literal.clone(), self::build(receivers, bucket, column + 1, columns))
+        })
+        .collect();
+
+    let types = type_keys
+        .iter()
+        .map(|type_id| {
+            let bucket = select(&|discriminant| match discriminant {
+                Discriminant::Literal(..)   => false,
+                Discriminant::Type(other)   => other == type_id,
+                Discriminant::Any           => true,
+            });
+
+            (type_id.clone(), self::build(receivers, bucket, column + 1, columns))
+        })
+        .collect();
+
+    let default = select(&|discriminant| matches!(discriminant, Discriminant::Any));
+
+    Node::Switch {
+        column,
+        literals,
+        types,
+        default: Box::new(self::build(receivers, default, column + 1, columns)),
+        all:     Box::new(self::build(receivers, indices, column + 1, columns)),
+    }
+}
+
+fn discriminant(receiver: &Receiver, column: usize) -> Discriminant {
+    let items = match &receiver.signature {
+        Some(pattern) => self::positional_items(pattern.clone()),
+        None          => return Discriminant::Any,
+    };
+
+    // Optional items may be skipped, which shifts the position of all following items.
+    if items.iter().take(column + 1).any(|item| item.is_optional()) {
+        return Discriminant::Any
+    }
+
+    match items.get(column) {
+        Some(Pattern::Value(ValuePattern { obj })) => match Literal::from_obj(obj) {
+            Some(literal) => Discriminant::Literal(literal, obj.get_type()),
+            None          => Discriminant::Any,
+        },
+
+        // Nameless variables don't check their type annotation.
+        Some(Pattern::Variable(VariablePattern { name: Some(_), type_id: Some(type_id) })) => {
+            Discriminant::Type(type_id.clone())
+        },
+
+        _ => Discriminant::Any,
+    }
+}
+
+/// Return the leading items of a pair chain which are matched by position rather than by name.
+fn positional_items(pattern: Pattern) -> Vec<Pattern> {
+    pattern
+        .flatten()
+        .into_iter()
+        .take_while(|item| !matches!(item, Pattern::Field(_)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::match_pattern_and_extract;
+    use crate::types::{FieldPattern, ListPattern, PairPattern, RangePattern, RestPattern};
+
+    fn value(kind: ObjKind) -> Pattern {
+        Pattern::Value(ValuePattern { obj: Box::new(Obj::new(kind)) })
+    }
+
+    fn int(n: i64) -> Pattern {
+        value(ObjKind::Int(n))
+    }
+
+    fn string(s: &str) -> Pattern {
+        value(ObjKind::String(String::from(s)))
+    }
+
+    fn variable(name: &str, type_id: Option<&str>) -> Pattern {
+        Pattern::Variable(VariablePattern {
+            name:    Some(String::from(name)),
+            type_id: type_id.map(String::from),
+        })
+    }
+
+    fn pair(left: Pattern, right: Pattern) -> Pattern {
+        Pattern::Pair(PairPattern { left: Box::new(left), right: Box::new(right) })
+    }
+
+    fn field(name: &str, value: Pattern, default: Option<ObjKind>) -> Pattern {
+        Pattern::Field(FieldPattern {
+            name:    String::from(name),
+            value:   Box::new(value),
+            default: default.map(|kind| Box::new(Obj::new(kind))),
+        })
+    }
+
+    fn rest(name: &str) -> Pattern {
+        Pattern::Rest(RestPattern { name: Some(String::from(name)) })
+    }
+
+    fn list_value(items: &[i64]) -> Pattern {
+        let list = Obj::list(items.iter().map(|n| Obj::new(ObjKind::Int(*n))).collect());
+
+        Pattern::Value(ValuePattern { obj: Box::new(list) })
+    }
+
+    /// Pick a receiver in the same way as `Multimethod::invoke`, using the tree to narrow down the candidates.
+    fn select(tree: &DecisionTree, receivers: &[Receiver], given: &Option<Pattern>) -> Option<usize> {
+        tree.candidates(receivers, given)
+            .into_iter()
+            .find(|index| match_pattern_and_extract(&receivers[*index].signature, given).is_ok())
+    }
+
+    /// Pick a receiver by trying every one of them in order of precedence.
+    fn select_linearly(receivers: &[Receiver], given: &Option<Pattern>) -> Option<usize> {
+        let mut order: Vec<usize> = (0..receivers.len()).collect();
+        order.sort_by(|a, b| receivers[*b].get_precedence().cmp(&receivers[*a].get_precedence()));
+
+        order
+            .into_iter()
+            .find(|index| match_pattern_and_extract(&receivers[*index].signature, given).is_ok())
+    }
+
+    #[test]
+    fn trees_select_the_same_receivers_as_linear_matching() {
+        let signatures = vec![
+            Some(variable("x", None)),
+            Some(int(0)),
+            Some(variable("n", Some("Int"))),
+            Some(variable("n", Some("Float"))),
+            None,
+            Some(Pattern::Range(RangePattern {
+                start:     Some(Box::new(Obj::new(ObjKind::Int(1)))),
+                end:       Some(Box::new(Obj::new(ObjKind::Int(10)))),
+                inclusive: false,
+            })),
+            Some(string("a")),
+            Some(pair(variable("x", Some("Int")), variable("y", Some("Int")))),
+            Some(pair(int(1), variable("y", None))),
+            Some(pair(variable("x", None), rest("others"))),
+            Some(field("retries", variable("n", None), Some(ObjKind::Int(3)))),
+            Some(pair(variable("x", None), field("to", variable("y", Some("String")), None))),
+            Some(Pattern::List(ListPattern { child: Some(Box::new(pair(variable("head", None), rest("tail")))) })),
+            Some(pair(value(ObjKind::Boolean(true)), pair(variable("x", None), variable("y", None)))),
+        ];
+
+        let receivers: Vec<Receiver> = signatures
+            .into_iter()
+            .enumerate()
+            .map(|(index, signature)| Receiver::from(signature, Box::new(Obj::new(ObjKind::Int(index as i64)))))
+            .collect();
+
+        let arguments = vec![
+            None,
+            Some(int(0)),
+            Some(int(5)),
+            Some(int(20)),
+            Some(value(ObjKind::Float(String::from("2.5")))),
+            Some(value(ObjKind::UInt(5))),
+            Some(string("a")),
+            Some(string("b")),
+            Some(value(ObjKind::Boolean(false))),
+            Some(pair(int(1), int(2))),
+            Some(pair(int(3), string("b"))),
+            Some(pair(int(1), pair(int(2), int(3)))),
+            Some(field("retries", int(5), None)),
+            Some(pair(int(1), field("to", string("b"), None))),
+            Some(list_value(&[1, 2])),
+            Some(list_value(&[])),
+            Some(pair(value(ObjKind::Boolean(true)), pair(int(1), int(2)))),
+        ];
+
+        // Every prefix of the receivers gives a differently shaped tree.
+        for count in 1..=receivers.len() {
+            let receivers = &receivers[..count];
+            let tree = DecisionTree::new(receivers);
+
+            for given in &arguments {
+                let expected = select_linearly(receivers, given);
+
+                // The second lookup is answered from the leaf's cache.
+                assert_eq!(select(&tree, receivers, given), expected, "{} receivers, arguments {:?}", count, given);
+                assert_eq!(select(&tree, receivers, given), expected, "{} receivers, arguments {:?}", count, given);
+            }
+        }
+    }
+}
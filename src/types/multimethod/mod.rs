@@ -1,130 +1,235 @@
+pub mod cache;
+pub mod dispatch;
 pub mod receiver;
 
+pub use self::cache::{DispatchCache, DispatchKey};
+pub use self::dispatch::DecisionTree;
 pub use self::receiver::Receiver;
 
-use magc::types::{
-    Expression,
-    ExpressionKind,
-    Call,
-};
+use std::sync::Arc;
 
 use magc::types::Pattern as MagcPattern;
+use magc::type_system::Typed;
 
 use crate::types::{
     Obj,
     ObjKind,
     Pattern,
+    Source,
+    ValuePattern,
 };
 
 use crate::interpreter::Interpreter;
 
 use crate::interpreter::InterpreterError;
+use crate::interpreter::diagnostic::Span;
+use crate::interpreter::trace::Frame;
 use super::Environment;
 
-use std::collections::HashMap;
-
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Multimethod {
+    pub name:      String,
     pub receivers: Vec<Receiver>,
+    /// The receivers compiled for fast selection, which is rebuilt whenever they change.
+    dispatch: Arc<DecisionTree>,
 }
 
 impl Multimethod {
-    pub fn from(signature: Option<Pattern>, body: Box<Obj>) -> Self {
-        let mut receivers = vec![];
+    pub fn from(name: String, signature: Option<Pattern>, body: Box<Obj>) -> Self {
+        Self::from_receiver(name, Receiver::from(signature, body))
+    }
 
-        receivers.push(Receiver::from(signature, body));
+    pub fn from_receiver(name: String, receiver: Receiver) -> Self {
+        let receivers = vec![receiver];
 
         Self {
+            name,
+            dispatch: Arc::new(DecisionTree::new(&receivers)),
             receivers,
         }
     }
 
-    /// Add a new receiver to this multimethod if it does not already exist.
-    pub fn define(&mut self, signature: Option<Pattern>, body: Box<Obj>) -> Result<(), InterpreterError> {
-        if let None = self.receivers.iter().find(|recv| recv.signature == signature) {
-            self.receivers.push(Receiver::from(signature, body));
+    /// Add a new receiver to this multimethod or replace the body of an existing one.
+    ///
+    /// If there already is a receiver with the same signature, it is replaced and returned.
+    pub fn define(&mut self, signature: Option<Pattern>, body: Box<Obj>) -> Option<Receiver> {
+        self.define_guarded(signature, None, body)
+    }
+
+    /// Add a new receiver which is only selected if the given guard evaluates to `true`.
+    ///
+    /// A receiver with the same signature and guard is replaced and returned.
+    pub fn define_guarded(&mut self, signature: Option<Pattern>, guard: Option<Box<Obj>>, body: Box<Obj>) -> Option<Receiver> {
+        self.define_receiver(Receiver::with_guard(signature, guard, body))
+    }
 
-            Ok(())
+    /// Add the given receiver, replacing and returning one with the same signature and guard.
+    pub fn define_receiver(&mut self, receiver: Receiver) -> Option<Receiver> {
+        let previous = if let Some(index) = self.position(&receiver.signature, &receiver.guard) {
+            Some(std::mem::replace(&mut self.receivers[index], receiver))
         } else {
-            Err(InterpreterError::MethodAlreadyExists)
+            self.receivers.push(receiver);
+
+            None
+        };
+
+        // The changed receiver may handle arguments which previously went elsewhere, so start over.
+        self.dispatch = Arc::new(DecisionTree::new(&self.receivers));
+
+        previous
+    }
+
+    /// Combine the receivers of two multimethods with the same name coming from different modules.
+    ///
+    /// Receivers which are identical in both are only kept once. Two different receivers
+    /// with the same signature and guard are a conflict, since neither should silently win.
+    pub fn merge(&self, other: &Multimethod) -> Result<Self, InterpreterError> {
+        let mut merged = self.clone();
+
+        for receiver in &other.receivers {
+            match merged.position(&receiver.signature, &receiver.guard) {
+                Some(index) if merged.receivers[index] == *receiver => (),
+                Some(_)                                              => return Err(InterpreterError::SignatureAlreadyExists),
+                None                                                 => merged.receivers.push(receiver.clone()),
+            }
         }
+
+        merged.dispatch = Arc::new(DecisionTree::new(&merged.receivers));
+
+        Ok(merged)
+    }
+
+    /// Return whether there is a receiver with exactly the given signature and guard.
+    pub fn has_receiver(&self, signature: &Option<Pattern>, guard: &Option<Box<Obj>>) -> bool {
+        self.position(signature, guard).is_some()
+    }
+
+    fn position(&self, signature: &Option<Pattern>, guard: &Option<Box<Obj>>) -> Option<usize> {
+        self.receivers
+            .iter()
+            .position(|recv| recv.signature == *signature && recv.guard == *guard)
     }
 
-    /// Try to find a matching receiver, run its body with the bound variables and return a value, if any.
+    /// Evaluate the arguments of a call and invoke the matching receiver with them.
+    ///
+    /// The call site is the location of the call expression, which is recorded in the call stack.
     pub fn call(&self,
         interpreter: &mut Interpreter,
         signature: Option<MagcPattern>,
-        optional_env: Option<Environment>
+        source: &Source,
+        optional_env: Option<Environment>,
+        call_site: Option<Span>,
     ) -> Result<Box<Obj>, InterpreterError> {
 
-        let evaluated_signature;
+        let arguments = self::evaluate_arguments(interpreter, signature, source, optional_env)?;
 
-        if let Some(magc_pattern) = signature {
-            let obj = interpreter.evaluate(
-                Box::new(
-                    Obj::new(ObjKind::Pattern(Pattern::from(magc_pattern)))
-                ),
-                optional_env,
-            )?;
+        self.invoke(interpreter, arguments, call_site)
+    }
 
-            evaluated_signature = Some(self::expect_pattern(*obj)?);
-        } else {
-            evaluated_signature = None;
-        };
+    /// Try to find a receiver matching the evaluated arguments, run its body with the bound variables and return a value, if any.
+    pub fn invoke(&self,
+        interpreter: &mut Interpreter,
+        arguments: Option<Pattern>,
+        call_site: Option<Span>,
+    ) -> Result<Box<Obj>, InterpreterError> {
 
-        // Find matching receivers and sort them so the one with the highest precedence value goes first.
-        let mut matching_receivers = self.find_matching_receivers(evaluated_signature)?;
+        let candidates = self.dispatch.candidates(&self.receivers, &arguments);
 
-        matching_receivers.sort_by(|a, b| b.2.cmp(&a.2));
+        // The candidates are already sorted by precedence, so the first match wins.
+        for index in candidates {
+            let receiver = &self.receivers[index];
 
-        if matching_receivers.len() >= 1 {
-            let (env, obj, _) = matching_receivers[0].clone();
+            if let Ok(env) = self::match_pattern_and_extract(&receiver.signature, &arguments) {
+                if let Some(guard) = &receiver.guard {
+                    if !self::evaluate_guard(interpreter, receiver, guard.clone(), env.clone())? {
+                        continue
+                    }
+                }
 
-            interpreter.evaluate(
-                obj,
-                Some(env),
-            )
-        } else {
-            return Err(InterpreterError::NoMatchingReceiver)
+                interpreter.call_stack.push(Frame::new(
+                    self.name.clone(),
+                    receiver.signature.as_ref().map(Pattern::to_string),
+                    call_site,
+                    interpreter.current_module.clone(),
+                ));
+
+                let result = self::evaluate_in_module(
+                    interpreter,
+                    receiver.module.clone(),
+                    receiver.body.clone(),
+                    env,
+                ).map_err(|e| e.traced(&interpreter.call_stack));
+
+                interpreter.call_stack.pop();
+
+                return result
+
+            }
         }
+
+        Err(self.explain_mismatch(&arguments))
     }
 
-    fn find_matching_receivers(&self,
-        reference_sig: Option<Pattern>,
-    ) -> Result<Vec<(Environment, Box<Obj>, usize)>, InterpreterError> {
+    /// Return the most specific reason why no receiver accepted the given arguments.
+    ///
+    /// Malformed arguments, like a repeated or unknown field, are reported as such instead
+    /// of as a missing receiver. The receivers are checked in order of precedence.
+    fn explain_mismatch(&self, arguments: &Option<Pattern>) -> InterpreterError {
+        let mut receivers: Vec<&Receiver> = self.receivers.iter().collect();
+        receivers.sort_by_key(|receiver| std::cmp::Reverse(receiver.get_precedence()));
 
-        self.receivers
-            .iter()
-            // Filter out any receivers which don't have a matching signature.
-            .filter(|recv| {
-                self::match_pattern(
-                    if let Some(s) = &recv.signature { Some(Pattern::from(s.clone())) } else { None },
-                    if let Some(s) = &reference_sig  { Some(Pattern::from(s.clone())) } else { None },
-                )
-            })
-            // Convert the matching receivers to a tuple containing the extracted variables,
-            // the body expression and the pattern's precedence to simplify sorting later.
-            .map(|recv| {
-                Ok((
-                    // Extract the variables which will be bound to function scope.
-                    self::match_pattern_and_extract(
-                        if let Some(s) = &recv.signature { Some(Pattern::from(s.clone())) } else { None },
-                        if let Some(s) = &reference_sig  { Some(Pattern::from(s.clone())) } else { None },
-                    ),
-                    recv.body.clone(),
-                    self::get_precedence(
-                        if let Some(s) = &reference_sig { Some(Pattern::from(s.clone())) } else { None },
-                    ),
-                ))
-            })
-            .collect()
+        receivers
+            .into_iter()
+            .filter_map(|receiver| self::match_pattern_and_extract(&receiver.signature, arguments).err())
+            .find(|error| *error != InterpreterError::NoMatch)
+            .unwrap_or(InterpreterError::NoMatchingReceiver)
     }
 }
 
-fn get_precedence(item: Option<Pattern>) -> usize {
-    match item {
-        Some(pattern) => pattern.get_precedence(),
-        None          => 0,
+/// Evaluate a receiver's guard in the scope of the variables bound by its signature.
+fn evaluate_guard(interpreter: &mut Interpreter, receiver: &Receiver, guard: Box<Obj>, env: Environment) -> Result<bool, InterpreterError> {
+    let obj = self::evaluate_in_module(interpreter, receiver.module.clone(), guard, env)?;
+
+    match obj.kind {
+        ObjKind::Boolean(boolean) => Ok(boolean),
+
+        _ => Err(InterpreterError::UnexpectedType {
+            expected: String::from("Boolean"),
+            found: obj.get_type(),
+        }),
+    }
+}
+
+/// Evaluate code of a receiver in a new frame, using the namespace of the module which defined it.
+fn evaluate_in_module(interpreter: &mut Interpreter, module: Option<String>, obj: Box<Obj>, env: Environment) -> Result<Box<Obj>, InterpreterError> {
+    let previous = interpreter.enter_module(module);
+    let result = interpreter.evaluate_in_frame(obj, env);
+    interpreter.enter_module(previous);
+
+    result
+}
+
+/// Evaluate the signature of a call into a pattern containing only values.
+pub(crate) fn evaluate_arguments(
+    interpreter: &mut Interpreter,
+    signature: Option<MagcPattern>,
+    source: &Source,
+    optional_env: Option<Environment>,
+) -> Result<Option<Pattern>, InterpreterError> {
+
+    match signature {
+        Some(magc_pattern) => {
+            let obj = interpreter.evaluate(
+                Box::new(
+                    Obj::new(ObjKind::Pattern(Pattern::from_checked(magc_pattern, source)?))
+                ),
+                optional_env,
+            )?;
+
+            Ok(Some(self::expect_pattern(*obj)?))
+        },
+
+        None => Ok(None),
     }
 }
 
@@ -132,26 +237,318 @@ fn expect_pattern(obj: Obj) -> Result<Pattern, InterpreterError> {
     match obj.kind {
         ObjKind::Pattern(pattern) => Ok(pattern),
 
-        // TODO: do correct error reporting here.
-        _ => return Err(InterpreterError::NoMatch)
+        // A single variable argument evaluates to its value, which is passed on as a value pattern.
+        _ => Ok(Pattern::Value(ValuePattern { obj: Box::new(obj) })),
     }
 }
 
-fn match_pattern(reference: Option<Pattern>, given: Option<Pattern>) -> bool {
+fn match_pattern_and_extract(reference: &Option<Pattern>, given: &Option<Pattern>) -> Result<Environment, InterpreterError> {
     match (reference, given) {
-        (None, None) => true,
-        (Some(r), Some(g)) => r.matches_with(g),
+        (None, None) => Ok(Environment::empty()),
+
+        (Some(r), Some(g)) => r.linearize(g.clone()),
+
+        // A call without arguments still matches if all fields of the signature have defaults.
+        (Some(r), None) if r.is_optional() => r.defaults(),
 
-        _ => false,
+        _ => Err(InterpreterError::NoMatch),
     }
 }
 
-fn match_pattern_and_extract(reference: Option<Pattern>, given: Option<Pattern>) -> Environment {
-    match (reference, given) {
-        (None, None) => Environment::empty(),
+#[cfg(test)]
+mod tests {
+    use magc::types::{Call, Expression, ExpressionKind};
 
-        (Some(r), Some(g)) => r.linearize(g).unwrap(),
+    use super::*;
+    use crate::types::{FieldPattern, PairPattern, RangePattern, VariablePattern};
 
-        _ => Environment::empty(),
+    fn value(kind: ObjKind) -> Pattern {
+        Pattern::Value(ValuePattern { obj: Box::new(Obj::new(kind)) })
     }
-}
\ No newline at end of file
+
+    fn variable(name: &str, type_id: Option<&str>) -> Pattern {
+        Pattern::Variable(VariablePattern {
+            name:    Some(String::from(name)),
+            type_id: type_id.map(String::from),
+        })
+    }
+
+    fn body(n: i64) -> Box<Obj> {
+        Box::new(Obj::new(ObjKind::Int(n)))
+    }
+
+    fn candidates(multimethod: &Multimethod, given: &Option<Pattern>) -> Vec<usize> {
+        multimethod.dispatch.candidates(&multimethod.receivers, given)
+    }
+
+    #[test]
+    fn values_holding_multimethods_are_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Multimethod>();
+        assert_send::<Obj>();
+    }
+
+    #[test]
+    fn defining_a_receiver_invalidates_cached_candidates() {
+        let mut multimethod = Multimethod::from(String::from("f"), Some(variable("n", Some("Int"))), body(0));
+        let given = Some(value(ObjKind::Int(1)));
+
+        assert_eq!(candidates(&multimethod, &given), vec![0]);
+
+        multimethod.define(Some(value(ObjKind::Int(1))), body(1));
+
+        assert_eq!(candidates(&multimethod, &given), vec![1, 0]);
+    }
+
+    #[test]
+    fn cached_candidates_keep_precedence_order() {
+        let range = Pattern::Range(RangePattern {
+            start:     Some(body(0)),
+            end:       Some(body(10)),
+            inclusive: false,
+        });
+
+        let mut multimethod = Multimethod::from(String::from("f"), Some(variable("n", None)), body(0));
+        multimethod.define(Some(range), body(1));
+        multimethod.define(Some(value(ObjKind::Int(1))), body(2));
+
+        let given = Some(value(ObjKind::Int(1)));
+
+        // The second lookup is answered by the cache of the same leaf.
+        assert_eq!(candidates(&multimethod, &given), vec![2, 1, 0]);
+        assert_eq!(candidates(&multimethod, &given), vec![2, 1, 0]);
+
+        let given = Some(value(ObjKind::Int(5)));
+
+        assert_eq!(candidates(&multimethod, &given), vec![1, 0]);
+        assert_eq!(candidates(&multimethod, &given), vec![1, 0]);
+    }
+
+    #[test]
+    fn candidates_stay_correct_when_the_cache_is_full() {
+        let mut multimethod = Multimethod::from(String::from("f"), Some(variable("x", None)), body(0));
+        multimethod.define(Some(field("f0", variable("n", None))), body(1));
+
+        let given = |n: usize| Some(field(&format!("f{}", n), value(ObjKind::Int(1))));
+        let expected = candidates(&multimethod, &given(0));
+
+        assert_eq!(expected.len(), 2);
+
+        // Every field name is a new argument shape, so the cache is cleared several times.
+        for n in 1..=cache::MAX_ENTRIES * 2 {
+            assert_eq!(candidates(&multimethod, &given(n)), vec![0]);
+        }
+
+        assert_eq!(candidates(&multimethod, &given(0)), expected);
+    }
+
+    fn reference(name: &str) -> Box<Obj> {
+        Box::new(Obj::new(ObjKind::Pattern(variable(name, None))))
+    }
+
+    /// Define `f(ok Boolean)` returning 0 and a receiver with the same signature and the given guard returning 1.
+    fn guarded(guard: Box<Obj>) -> Multimethod {
+        let signature = Some(variable("ok", Some("Boolean")));
+
+        let mut multimethod = Multimethod::from(String::from("f"), signature.clone(), body(0));
+        multimethod.define_guarded(signature, Some(guard), body(1));
+
+        multimethod
+    }
+
+    fn invoke(multimethod: &Multimethod, argument: ObjKind) -> Result<Box<Obj>, InterpreterError> {
+        multimethod.invoke(&mut Interpreter::new(), Some(value(argument)), None)
+    }
+
+    #[test]
+    fn guarded_receivers_win_when_their_guard_holds() {
+        // The guarded receiver is defined last, but goes before the unguarded one with the same signature.
+        assert_eq!(invoke(&guarded(reference("ok")), ObjKind::Boolean(true)), Ok(body(1)));
+    }
+
+    #[test]
+    fn false_guards_fall_back_to_the_next_receiver() {
+        assert_eq!(invoke(&guarded(reference("ok")), ObjKind::Boolean(false)), Ok(body(0)));
+    }
+
+    #[test]
+    fn guards_must_evaluate_to_a_boolean() {
+        assert_eq!(
+            invoke(&guarded(body(5)), ObjKind::Boolean(true)),
+            Err(InterpreterError::UnexpectedType {
+                expected: String::from("Boolean"),
+                found:    Some(String::from("Int")),
+            }),
+        );
+    }
+
+    /// Evaluate every expression in the given source and return the value of the last one.
+    fn run(source: &str) -> Result<Box<Obj>, InterpreterError> {
+        let mut interpreter = Interpreter::new();
+        let mut result = Ok(Box::new(Obj::new(ObjKind::Nothing)));
+
+        for obj in crate::interpreter::loader::parse(source, "test")? {
+            result = interpreter.evaluate(Box::new(obj), None);
+        }
+
+        result
+    }
+
+    #[test]
+    fn guards_written_in_source_select_receivers() {
+        let source = "
+            def fact(n Int) where n > 0 n * fact(n - 1)
+            def fact(n Int) 1
+
+            def sign(n Int) where n > 0 \"positive\"
+            def sign(n Int) where n < 0 \"negative\"
+            def sign(n Int) \"zero\"
+        ";
+
+        assert_eq!(run(&format!("{} fact(5)", source)), Ok(body(120)));
+
+        let sign = |n: &str| run(&format!("{} sign({})", source, n));
+
+        assert_eq!(sign("3"), Ok(Box::new(Obj::new(ObjKind::String(String::from("positive"))))));
+        assert_eq!(sign("0 - 3"), Ok(Box::new(Obj::new(ObjKind::String(String::from("negative"))))));
+        assert_eq!(sign("0"), Ok(Box::new(Obj::new(ObjKind::String(String::from("zero"))))));
+    }
+
+    fn field(name: &str, value: Pattern) -> Pattern {
+        Pattern::Field(FieldPattern { name: String::from(name), value: Box::new(value), default: None })
+    }
+
+    fn pair(left: Pattern, right: Pattern) -> Pattern {
+        Pattern::Pair(PairPattern { left: Box::new(left), right: Box::new(right) })
+    }
+
+    fn int(n: i64) -> Pattern {
+        value(ObjKind::Int(n))
+    }
+
+    /// Define `f(a: x, b: y)` and call it with the given arguments.
+    fn call_with_fields(arguments: Pattern) -> Result<Box<Obj>, InterpreterError> {
+        let signature = pair(field("a", variable("x", None)), field("b", variable("y", None)));
+        let multimethod = Multimethod::from(String::from("f"), Some(signature), body(0));
+
+        multimethod.invoke(&mut Interpreter::new(), Some(arguments), None)
+    }
+
+    #[test]
+    fn repeated_fields_are_reported() {
+        assert_eq!(
+            call_with_fields(pair(field("a", int(1)), field("a", int(2)))),
+            Err(InterpreterError::DuplicateField { name: String::from("a") }),
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_reported() {
+        assert_eq!(
+            call_with_fields(pair(field("a", int(1)), pair(field("b", int(2)), field("c", int(3))))),
+            Err(InterpreterError::UnknownField { name: String::from("c") }),
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        assert_eq!(
+            call_with_fields(field("a", int(1))),
+            Err(InterpreterError::MissingField { name: String::from("b") }),
+        );
+    }
+
+    #[test]
+    fn positional_arguments_after_fields_are_reported() {
+        let signature = pair(variable("y", None), field("a", variable("x", None)));
+        let multimethod = Multimethod::from(String::from("f"), Some(signature), body(0));
+
+        assert_eq!(
+            multimethod.invoke(&mut Interpreter::new(), Some(pair(field("a", int(1)), int(2))), None),
+            Err(InterpreterError::PositionalAfterField),
+        );
+    }
+
+    #[test]
+    fn mismatched_values_report_a_missing_receiver() {
+        let multimethod = Multimethod::from(String::from("f"), Some(variable("n", Some("Int"))), body(0));
+
+        assert_eq!(
+            multimethod.invoke(&mut Interpreter::new(), Some(value(ObjKind::Boolean(true))), None),
+            Err(InterpreterError::NoMatchingReceiver),
+        );
+    }
+
+    fn call(name: &str, start: usize) -> Box<Obj> {
+        Box::new(Obj::new(ObjKind::Expression(Expression {
+            kind:      ExpressionKind::Call(Call { name: String::from(name), signature: None }),
+            start_pos: start,
+            end_pos:   start + name.len() + 2,
+        }, Source::default())))
+    }
+
+    /// Define `outer()` calling `middle()` calling `inner()`, which refers to an undefined variable.
+    fn failing_calls() -> (Interpreter, Multimethod) {
+        let mut interpreter = Interpreter::new();
+
+        for (name, body) in [("inner", reference("missing")), ("middle", call("inner", 20))] {
+            let multimethod = Multimethod::from(String::from(name), None, body);
+
+            interpreter
+                .define_variable(VariablePattern::from_name(String::from(name)), Obj::new(ObjKind::Multimethod(multimethod)))
+                .unwrap();
+        }
+
+        (interpreter, Multimethod::from(String::from("outer"), None, call("middle", 10)))
+    }
+
+    /// Count the call stacks attached to an error and the errors it wraps.
+    fn traces(error: &InterpreterError) -> usize {
+        match error {
+            InterpreterError::Traced { error, .. }  => 1 + traces(error),
+            InterpreterError::Located { error, .. } => traces(error),
+
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn frames_are_popped_when_a_call_fails() {
+        let (mut interpreter, outer) = failing_calls();
+
+        assert!(outer.invoke(&mut interpreter, None, None).is_err());
+        assert!(interpreter.call_stack.is_empty());
+    }
+
+    #[test]
+    fn the_call_stack_is_attached_once_where_the_error_escapes_first() {
+        let (mut interpreter, outer) = failing_calls();
+        let error = outer.invoke(&mut interpreter, None, None).unwrap_err();
+
+        assert_eq!(traces(&error), 1);
+        assert_eq!(
+            error.inner(),
+            &InterpreterError::NoMatchingVariable { variable_pattern: VariablePattern::from_name(String::from("missing")) },
+        );
+    }
+
+    #[test]
+    fn the_call_stack_lists_the_outermost_call_first() {
+        let (mut interpreter, outer) = failing_calls();
+        let error = outer.invoke(&mut interpreter, None, None).unwrap_err();
+
+        let trace: Vec<(&str, Option<Span>)> = error
+            .trace()
+            .unwrap()
+            .iter()
+            .map(|frame| (frame.name.as_str(), frame.call_site))
+            .collect();
+
+        assert_eq!(trace, vec![
+            ("outer",  None),
+            ("middle", Some(Span::new(10, 18))),
+            ("inner",  Some(Span::new(20, 27))),
+        ]);
+    }
+}
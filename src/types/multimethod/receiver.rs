@@ -3,14 +3,54 @@ use crate::types::{Obj, Pattern};
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Receiver {
     pub signature: Option<Pattern>,
+    /// An optional condition which is evaluated with the variables bound by the signature.
+    ///
+    /// The receiver is only selected if the guard evaluates to `true`, like `n > 0` in
+    /// `def fact(n Int) where n > 0`.
+    pub guard:     Option<Box<Obj>>,
     pub body:      Box<Obj>,
+    /// The module which defined this receiver, whose namespace is used for its guard and body.
+    ///
+    /// Receivers defined by the main program have no module.
+    pub module:    Option<String>,
 }
 
 impl Receiver {
     pub fn from(signature: Option<Pattern>, body: Box<Obj>) -> Self {
         Self {
             signature,
+            guard: None,
             body,
+            module: None,
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_guard(signature: Option<Pattern>, guard: Option<Box<Obj>>, body: Box<Obj>) -> Self {
+        Self {
+            signature,
+            guard,
+            body,
+            module: None,
+        }
+    }
+
+    pub fn in_module(self, module: Option<String>) -> Self {
+        Self {
+            module,
+            ..self
+        }
+    }
+
+    /// Return a value used to decide which of several matching receivers is called.
+    ///
+    /// Receivers with more specific patterns go first, and a guarded receiver goes before
+    /// an unguarded one with the same pattern.
+    pub fn get_precedence(&self) -> (usize, bool) {
+        let pattern_precedence = match &self.signature {
+            Some(pattern) => pattern.get_precedence(),
+            None          => 0,
+        };
+
+        (pattern_precedence, self.guard.is_some())
+    }
+}
@@ -1,11 +1,9 @@
+use std::cmp::Ordering;
 use std::ops::{Add, Sub, Mul, Div};
 
 use super::*;
 
-use crate::interpreter::{
-    InterpreterResult,
-    InterpreterError,
-};
+use crate::interpreter::InterpreterError;
 
 impl Add for Obj {
     type Output = Result<Self, InterpreterError>;
@@ -40,94 +38,195 @@ impl Div for Obj {
 }
 
 fn add(o1: Obj, o2: Obj) -> Result<Obj, InterpreterError> {
-    let kind = match (o1.kind.clone(), o2.kind.clone()) {
-        (ObjKind::Int(n1), ObjKind::Int(n2))     => ObjKind::Int(n1 + n2),
-        (ObjKind::Int(n1), ObjKind::UInt(n2))    => ObjKind::Int(n1 + n2 as i64),
-        (ObjKind::Int(n1), ObjKind::Float(n2))   => ObjKind::Float(format!("{}", n1 as f64 + n2.parse::<f64>().unwrap())),
-
-        (ObjKind::UInt(n1), ObjKind::Int(n2))    => ObjKind::UInt(n1 + n2 as u64),
-        (ObjKind::UInt(n1), ObjKind::UInt(n2))   => ObjKind::UInt(n1 + n2),
-        (ObjKind::UInt(n1), ObjKind::Float(n2))  => ObjKind::Float(format!("{}", n1 as f64 + n2.parse::<f64>().unwrap())),
-
-        (ObjKind::Float(n1), ObjKind::Int(n2))   => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() + n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::UInt(n2))  => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() + n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::Float(n2)) => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() + n2.parse::<f64>().unwrap())),
-
-        _ => return Err(InterpreterError::UnexpectedType {
-            expected: String::from("Int | UInt | Float"),
-            found: Some(format!("({:?}, {:?})", o1, o2)),
-        })
-    };
-
-    Ok(Obj::new(kind))
+    self::apply(o1, o2, i128::checked_add, |n1, n2| n1 + n2)
 }
 
 fn sub(o1: Obj, o2: Obj) -> Result<Obj, InterpreterError> {
-    let kind = match (o1.kind.clone(), o2.kind.clone()) {
-        (ObjKind::Int(n1), ObjKind::Int(n2))     => ObjKind::Int(n1 - n2),
-        (ObjKind::Int(n1), ObjKind::UInt(n2))    => ObjKind::Int(n1 - n2 as i64),
-        (ObjKind::Int(n1), ObjKind::Float(n2))   => ObjKind::Float(format!("{}", n1 as f64 - n2.parse::<f64>().unwrap())),
-
-        (ObjKind::UInt(n1), ObjKind::Int(n2))    => ObjKind::UInt(n1 - n2 as u64),
-        (ObjKind::UInt(n1), ObjKind::UInt(n2))   => ObjKind::UInt(n1 - n2),
-        (ObjKind::UInt(n1), ObjKind::Float(n2))  => ObjKind::Float(format!("{}", n1 as f64 - n2.parse::<f64>().unwrap())),
-
-        (ObjKind::Float(n1), ObjKind::Int(n2))   => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() - n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::UInt(n2))  => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() - n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::Float(n2)) => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() - n2.parse::<f64>().unwrap())),
-
-        _ => return Err(InterpreterError::UnexpectedType {
-            expected: String::from("Int | UInt | Float"),
-            found: Some(format!("({:?}, {:?})", o1, o2)),
-        })
-    };
-
-    Ok(Obj::new(kind))
+    self::apply(o1, o2, i128::checked_sub, |n1, n2| n1 - n2)
 }
 
 fn mul(o1: Obj, o2: Obj) -> Result<Obj, InterpreterError> {
-    let kind = match (o1.kind.clone(), o2.kind.clone()) {
-        (ObjKind::Int(n1), ObjKind::Int(n2))     => ObjKind::Int(n1 * n2),
-        (ObjKind::Int(n1), ObjKind::UInt(n2))    => ObjKind::Int(n1 * n2 as i64),
-        (ObjKind::Int(n1), ObjKind::Float(n2))   => ObjKind::Float(format!("{}", n1 as f64 * n2.parse::<f64>().unwrap())),
-
-        (ObjKind::UInt(n1), ObjKind::Int(n2))    => ObjKind::UInt(n1 * n2 as u64),
-        (ObjKind::UInt(n1), ObjKind::UInt(n2))   => ObjKind::UInt(n1 * n2),
-        (ObjKind::UInt(n1), ObjKind::Float(n2))  => ObjKind::Float(format!("{}", n1 as f64 * n2.parse::<f64>().unwrap())),
-
-        (ObjKind::Float(n1), ObjKind::Int(n2))   => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() * n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::UInt(n2))  => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() * n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::Float(n2)) => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() * n2.parse::<f64>().unwrap())),
-
-        _ => return Err(InterpreterError::UnexpectedType {
-            expected: String::from("Int | UInt | Float"),
-            found: Some(format!("({:?}, {:?})", o1, o2)),
-        })
-    };
-
-    Ok(Obj::new(kind))
+    self::apply(o1, o2, i128::checked_mul, |n1, n2| n1 * n2)
 }
 
 fn div(o1: Obj, o2: Obj) -> Result<Obj, InterpreterError> {
-    let kind = match (o1.kind.clone(), o2.kind.clone()) {
-        (ObjKind::Int(n1), ObjKind::Int(n2))     => ObjKind::Int(n1 / n2),
-        (ObjKind::Int(n1), ObjKind::UInt(n2))    => ObjKind::Int(n1 / n2 as i64),
-        (ObjKind::Int(n1), ObjKind::Float(n2))   => ObjKind::Float(format!("{}", n1 as f64 / n2.parse::<f64>().unwrap())),
-
-        (ObjKind::UInt(n1), ObjKind::Int(n2))    => ObjKind::UInt(n1 / n2 as u64),
-        (ObjKind::UInt(n1), ObjKind::UInt(n2))   => ObjKind::UInt(n1 / n2),
-        (ObjKind::UInt(n1), ObjKind::Float(n2))  => ObjKind::Float(format!("{}", n1 as f64 / n2.parse::<f64>().unwrap())),
-
-        (ObjKind::Float(n1), ObjKind::Int(n2))   => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() / n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::UInt(n2))  => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() / n2 as f64)),
-        (ObjKind::Float(n1), ObjKind::Float(n2)) => ObjKind::Float(format!("{}", n1.parse::<f64>().unwrap() / n2.parse::<f64>().unwrap())),
-
-        _ => return Err(InterpreterError::UnexpectedType {
-            expected: String::from("Int | UInt | Float"),
-            found: Some(format!("({:?}, {:?})", o1, o2)),
-        })
+    if let (ObjKind::Int(_) | ObjKind::UInt(_), ObjKind::Int(0) | ObjKind::UInt(0)) = (&o1.kind, &o2.kind) {
+        return Err(InterpreterError::DivisionByZero)
+    }
+
+    self::apply(o1, o2, i128::checked_div, |n1, n2| n1 / n2)
+}
+
+/// Apply an arithmetic operation to two numbers.
+///
+/// Integers are computed with enough room for any pair of operands and keep the type
+/// of the left one, so results which don't fit into it are reported as an overflow.
+/// As soon as a float is involved, both numbers are converted to floats.
+fn apply(
+    o1: Obj,
+    o2: Obj,
+    int_op: fn(i128, i128) -> Option<i128>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Obj, InterpreterError> {
+
+    let kind = match (&o1.kind, &o2.kind) {
+        (ObjKind::Int(n1), ObjKind::Int(n2))   => self::int(int_op(*n1 as i128, *n2 as i128))?,
+        (ObjKind::Int(n1), ObjKind::UInt(n2))  => self::int(int_op(*n1 as i128, *n2 as i128))?,
+        (ObjKind::UInt(n1), ObjKind::Int(n2))  => self::uint(int_op(*n1 as i128, *n2 as i128))?,
+        (ObjKind::UInt(n1), ObjKind::UInt(n2)) => self::uint(int_op(*n1 as i128, *n2 as i128))?,
+
+        _ => match (self::to_float(&o1)?, self::to_float(&o2)?) {
+            (Some(n1), Some(n2)) => ObjKind::Float(self::float_lexeme(float_op(n1, n2))),
+
+            _ => return Err(InterpreterError::UnexpectedType {
+                expected: String::from("Int | UInt | Float"),
+                found: self::mismatched_operand(&o1, &o2, |obj| self::as_float(obj).is_some()).get_type(),
+            }),
+        },
     };
 
     Ok(Obj::new(kind))
 }
 
+/// Return the text of a computed float, which always has a decimal point or an exponent, like `2.0` or `1e20`.
+///
+/// Without them, a whole number like `2` would read back as an integer.
+fn float_lexeme(n: f64) -> String {
+    format!("{:?}", n)
+}
+
+fn int(result: Option<i128>) -> Result<ObjKind, InterpreterError> {
+    match result.map(i64::try_from) {
+        Some(Ok(n)) => Ok(ObjKind::Int(n)),
+        _           => Err(InterpreterError::IntegerOverflow),
+    }
+}
+
+fn uint(result: Option<i128>) -> Result<ObjKind, InterpreterError> {
+    match result.map(u64::try_from) {
+        Some(Ok(n)) => Ok(ObjKind::UInt(n)),
+        _           => Err(InterpreterError::IntegerOverflow),
+    }
+}
+
+/// Convert a number into a float, failing if a float has a malformed lexeme.
+fn to_float(obj: &Obj) -> Result<Option<f64>, InterpreterError> {
+    match &obj.kind {
+        ObjKind::Float(lexeme) => match lexeme.parse::<f64>() {
+            Ok(n)  => Ok(Some(n)),
+            Err(_) => Err(InterpreterError::InvalidLiteral {
+                expected: String::from("Float"),
+                lexeme:   lexeme.clone(),
+            }),
+        },
+
+        _ => Ok(self::as_float(obj)),
+    }
+}
+
+/// Compare two numbers, promoting them to a common type if necessary, or two characters.
+pub fn compare(o1: &Obj, o2: &Obj) -> Result<Ordering, InterpreterError> {
+    match (&o1.kind, &o2.kind) {
+        (ObjKind::Int(n1), ObjKind::Int(n2))     => return Ok(n1.cmp(n2)),
+        (ObjKind::Int(n1), ObjKind::UInt(n2))    => return Ok((*n1 as i128).cmp(&(*n2 as i128))),
+        (ObjKind::UInt(n1), ObjKind::Int(n2))    => return Ok((*n1 as i128).cmp(&(*n2 as i128))),
+        (ObjKind::UInt(n1), ObjKind::UInt(n2))   => return Ok(n1.cmp(n2)),
+
+        // Characters are represented as strings with a single character in them.
+        (ObjKind::String(s1), ObjKind::String(s2)) if self::is_char(s1) && self::is_char(s2) => return Ok(s1.cmp(s2)),
+
+        _ => (),
+    }
+
+    if let (Some(n1), Some(n2)) = (self::as_float(o1), self::as_float(o2)) {
+        if let Some(ordering) = n1.partial_cmp(&n2) {
+            return Ok(ordering)
+        }
+    }
+
+    Err(InterpreterError::UnexpectedType {
+        expected: String::from("Int | UInt | Float | Character"),
+        found: self::mismatched_operand(o1, o2, |obj| match &obj.kind {
+            ObjKind::String(s) => self::is_char(s),
+            _                  => self::as_float(obj).is_some(),
+        }).get_type(),
+    })
+}
+
+/// Return the first operand which isn't accepted, or the right one if both are but don't go together.
+fn mismatched_operand<'a>(o1: &'a Obj, o2: &'a Obj, accepts: fn(&Obj) -> bool) -> &'a Obj {
+    if accepts(o1) { o2 } else { o1 }
+}
+
+fn as_float(obj: &Obj) -> Option<f64> {
+    match &obj.kind {
+        ObjKind::Int(n)   => Some(*n as f64),
+        ObjKind::UInt(n)  => Some(*n as f64),
+        ObjKind::Float(n) => n.parse::<f64>().ok(),
+
+        _ => None,
+    }
+}
+
+fn is_char(string: &str) -> bool {
+    string.chars().count() == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> Obj {
+        Obj::new(ObjKind::Int(n))
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error() {
+        assert_eq!(int(i64::MAX) + int(1), Err(InterpreterError::IntegerOverflow));
+        assert_eq!(int(i64::MIN) / int(-1), Err(InterpreterError::IntegerOverflow));
+        assert_eq!(Obj::new(ObjKind::UInt(1)) - int(2), Err(InterpreterError::IntegerOverflow));
+    }
+
+    #[test]
+    fn malformed_floats_are_an_error() {
+        assert_eq!(
+            int(1) + Obj::new(ObjKind::Float(String::from("1.2.3"))),
+            Err(InterpreterError::InvalidLiteral {
+                expected: String::from("Float"),
+                lexeme:   String::from("1.2.3"),
+            }),
+        );
+    }
+
+    #[test]
+    fn computed_floats_keep_their_decimal_point() {
+        let float = |lexeme: &str| Obj::new(ObjKind::Float(String::from(lexeme)));
+
+        assert_eq!((float("1.5") + float("0.5")).map(|obj| obj.kind), Ok(ObjKind::Float(String::from("2.0"))));
+        assert_eq!((int(2) * float("1.0")).map(|obj| obj.kind), Ok(ObjKind::Float(String::from("2.0"))));
+        assert_eq!((float("1e20") * int(10)).map(|obj| obj.kind), Ok(ObjKind::Float(String::from("1e21"))));
+    }
+
+    #[test]
+    fn mismatched_operands_report_their_type() {
+        let boolean = || Obj::new(ObjKind::Boolean(true));
+        let string  = |s: &str| Obj::new(ObjKind::String(String::from(s)));
+
+        assert_eq!(
+            int(1) + boolean(),
+            Err(InterpreterError::UnexpectedType {
+                expected: String::from("Int | UInt | Float"),
+                found:    Some(String::from("Boolean")),
+            }),
+        );
+
+        let comparison_error = |found: &str| Err(InterpreterError::UnexpectedType {
+            expected: String::from("Int | UInt | Float | Character"),
+            found:    Some(String::from(found)),
+        });
+
+        assert_eq!(compare(&string("ab"), &int(1)), comparison_error("String"));
+        assert_eq!(compare(&string("a"), &int(1)), comparison_error("Int"));
+        assert_eq!(compare(&string("a"), &string("b")), Ok(Ordering::Less));
+    }
+}
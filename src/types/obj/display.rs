@@ -0,0 +1,495 @@
+//! Printing objects in Mag syntax.
+//!
+//! Values are printed as the literal which creates them, so printing a value and
+//! parsing it again gives an equal value. Unsigned integers and errors have no literal
+//! and are printed as a call to the builtin `uint` or `error` constructor. Things which
+//! cannot be created from source, like multimethods and modules, are printed in angle brackets.
+
+use std::fmt::{Display, Formatter, Result};
+
+use magc::types::{Expression, ExpressionKind, TokenKind};
+use magc::types::Pattern as MagcPattern;
+
+use crate::types::{ImportKind, MatchArm, Pattern};
+
+use super::{Obj, ObjKind, Operator, Source};
+
+impl Display for Obj {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self.kind {
+            ObjKind::Int(int)         => write!(f, "{}", int),
+            ObjKind::UInt(uint)       => write!(f, "uint({})", uint),
+            ObjKind::Float(float)     => self::write_float(f, float),
+            ObjKind::Boolean(boolean) => write!(f, "{}", boolean),
+            ObjKind::String(string)   => self::write_string(f, string),
+            ObjKind::Type(type_id)    => write!(f, "{}", type_id),
+            ObjKind::Pattern(pattern) => write!(f, "{}", pattern),
+            ObjKind::Nothing          => write!(f, "nothing"),
+
+            ObjKind::List(_) => {
+                write!(f, "[")?;
+                self::write_separated(f, &self.list_items().unwrap_or_default(), ", ")?;
+                write!(f, "]")
+            },
+
+            ObjKind::Multimethod(multimethod) => {
+                let count = multimethod.receivers.len();
+                let plural = if count == 1 { "" } else { "s" };
+
+                write!(f, "<multimethod {} with {} receiver{}>", multimethod.name, count, plural)
+            },
+
+            ObjKind::Module(module) => write!(f, "<module {}>", module.name),
+
+            ObjKind::Expression(expression, source) => self::write_expression(f, expression, source),
+
+            ObjKind::Match(match_expression) => {
+                write!(f, "match {} ", match_expression.value)?;
+                self::write_arms(f, &match_expression.arms)
+            },
+
+            ObjKind::Definition(definition) => {
+                let keyword = if definition.mutable { "let mut" } else { "let" };
+
+                write!(f, "{} {} = {}", keyword, definition.pattern, definition.value)
+            },
+
+            ObjKind::Assignment(assignment) => write!(f, "{} = {}", assignment.name, assignment.value),
+
+            ObjKind::Sequence(sequence) => {
+                if sequence.expressions.is_empty() {
+                    return write!(f, "{{}}")
+                }
+
+                write!(f, "{{ ")?;
+                self::write_separated(f, &sequence.expressions, "; ")?;
+                write!(f, " }}")
+            },
+
+            ObjKind::Import(import) => {
+                write!(f, "import {}", import.module)?;
+
+                match &import.kind {
+                    ImportKind::Qualified { alias: Some(alias) } => write!(f, " as {}", alias),
+                    ImportKind::Qualified { alias: None }        => Ok(()),
+                    ImportKind::Selective { names }              => write!(f, " ({})", names.join(", ")),
+                }
+            },
+
+            ObjKind::Error(error) => {
+                write!(f, "error(")?;
+                self::write_string(f, &error.kind)?;
+                write!(f, ", ")?;
+                self::write_string(f, &error.message)?;
+
+                match error.payload.kind {
+                    ObjKind::Nothing => write!(f, ")"),
+                    _                => write!(f, ", {})", error.payload),
+                }
+            },
+            ObjKind::Throw(throw) => write!(f, "throw {}", throw.value),
+
+            ObjKind::Try(try_expression) => {
+                write!(f, "try {} catch ", try_expression.body)?;
+                self::write_arms(f, &try_expression.arms)
+            },
+        }
+    }
+}
+
+fn write_separated(f: &mut Formatter<'_>, objs: &[Obj], separator: &str) -> Result {
+    for (index, obj) in objs.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", separator)?;
+        }
+
+        write!(f, "{}", obj)?;
+    }
+
+    Ok(())
+}
+
+fn write_arms(f: &mut Formatter<'_>, arms: &[MatchArm]) -> Result {
+    write!(f, "{{ ")?;
+
+    for (index, arm) in arms.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{} => {}", arm.pattern, arm.body)?;
+    }
+
+    write!(f, " }}")
+}
+
+/// Write a float literal, adding a decimal point to a whole number like `2` so it does not read back as an integer.
+fn write_float(f: &mut Formatter<'_>, float: &str) -> Result {
+    match float.parse::<f64>() {
+        Ok(n) if n.is_finite() && !float.contains(['.', 'e', 'E']) => write!(f, "{}.0", float),
+        _                                                          => write!(f, "{}", float),
+    }
+}
+
+/// Write a string literal, escaping quotes, backslashes and control characters.
+fn write_string(f: &mut Formatter<'_>, string: &str) -> Result {
+    write!(f, "\"")?;
+
+    for c in string.chars() {
+        match c {
+            '"'  => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+
+            c if c.is_control() => write!(f, "{}", c.escape_unicode())?,
+            c                   => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+/// Print an expression back as source code.
+fn write_expression(f: &mut Formatter<'_>, expression: &Expression, source: &Source) -> Result {
+    match &expression.kind {
+        // Literals, including the quotes of strings, are printed as they were written.
+        ExpressionKind::Literal(_) => write!(f, "{}", source.lexeme(expression)),
+
+        ExpressionKind::Type(type_id)    => write!(f, "{}", type_id),
+        ExpressionKind::Pattern(pattern) => self::write_pattern(f, pattern, source),
+
+        ExpressionKind::List(child) => {
+            write!(f, "[")?;
+
+            if let Some(child) = child {
+                self::write_expression(f, child, source)?;
+            }
+
+            write!(f, "]")
+        },
+
+        ExpressionKind::Call(call) => {
+            write!(f, "{}(", call.name)?;
+
+            if let Some(signature) = &call.signature {
+                self::write_pattern(f, signature, source)?;
+            }
+
+            write!(f, ")")
+        },
+
+        ExpressionKind::Method(method) => {
+            write!(f, "def {}(", method.name)?;
+
+            if let Some(signature) = &method.signature {
+                self::write_pattern(f, signature, source)?;
+            }
+
+            write!(f, ")")?;
+
+            // A body starting on the next line is a block which ends with `end`.
+            match &method.body.kind {
+                ExpressionKind::Block(block) => {
+                    writeln!(f)?;
+                    self::write_lines(f, &block.children, source)?;
+                    write!(f, "end")
+                },
+
+                _ => write!(f, " {}", Printed(&method.body, source)),
+            }
+        },
+
+        ExpressionKind::Conditional(conditional) => {
+            write!(f, "if ")?;
+            self::write_expression(f, &conditional.condition, source)?;
+            write!(f, " then")?;
+
+            // Arms starting on the next line are blocks, and the conditional ends with `end`.
+            if let ExpressionKind::Block(then_block) = &conditional.then_arm.kind {
+                writeln!(f)?;
+                self::write_lines(f, &then_block.children, source)?;
+
+                if let Some(else_arm) = &conditional.else_arm {
+                    writeln!(f, "else")?;
+
+                    match &else_arm.kind {
+                        ExpressionKind::Block(else_block) => self::write_lines(f, &else_block.children, source)?,
+                        _                                 => self::write_lines(f, std::slice::from_ref(else_arm), source)?,
+                    }
+                }
+
+                return write!(f, "end")
+            }
+
+            write!(f, " {}", Printed(&conditional.then_arm, source))?;
+
+            if let Some(else_arm) = &conditional.else_arm {
+                write!(f, " else {}", Printed(else_arm, source))?;
+            }
+
+            Ok(())
+        },
+
+        ExpressionKind::Match(match_expression) => {
+            writeln!(f, "match {}", Printed(&match_expression.subject, source))?;
+
+            for arm in &match_expression.arms {
+                let mut printed = format!("case {}", PrintedPattern(&arm.pattern, source));
+
+                // A body starting on the next line is a block which ends with `end`.
+                match &arm.body.kind {
+                    ExpressionKind::Block(block) => {
+                        printed.push_str(" then\n");
+
+                        for child in &block.children {
+                            printed.push_str(&self::indent(&Printed(child, source).to_string()));
+                        }
+
+                        printed.push_str("end");
+                    },
+
+                    _ => printed.push_str(&format!(" then {}", Printed(&arm.body, source))),
+                }
+
+                write!(f, "{}", self::indent(&printed))?;
+            }
+
+            if let Some(else_arm) = &match_expression.else_arm {
+                write!(f, "{}", self::indent(&format!("else {}", Printed(else_arm, source))))?;
+            }
+
+            write!(f, "end")
+        },
+
+        ExpressionKind::Block(block) => {
+            writeln!(f, "do")?;
+            self::write_lines(f, &block.children, source)?;
+            write!(f, "end")
+        },
+
+        ExpressionKind::Infix(infix) => {
+            let operator = Operator::from_token(&infix.operator.kind);
+
+            // Operators are left associative, so an operand on the right needs parentheses
+            // even if its operator binds as tightly as this one.
+            self::write_operand(f, &infix.left, source, operator, false)?;
+
+            match operator {
+                Some(operator) => write!(f, " {} ", operator.symbol())?,
+
+                // An operator without a known symbol is printed as it was written.
+                None => write!(f, " {} ", source.text(infix.operator.start_pos, infix.operator.end_pos))?,
+            }
+
+            self::write_operand(f, &infix.right, source, operator, true)
+        },
+
+        ExpressionKind::Prefix(prefix) => {
+            match prefix.operator.kind {
+                TokenKind::Bang  => write!(f, "!")?,
+                TokenKind::Minus => write!(f, "-")?,
+                TokenKind::Plus  => write!(f, "+")?,
+                _                => write!(f, "{}", source.text(prefix.operator.start_pos, prefix.operator.end_pos))?,
+            }
+
+            // Prefix operators bind tighter than any infix operator. There is no grouping in
+            // the grammar yet, so an infix operand can only be printed in parentheses.
+            self::write_operand(f, &prefix.operand, source, None, false)
+        },
+
+        ExpressionKind::Var(var) => write!(f, "var {} = {}", var.name, Printed(&var.value, source)),
+
+        ExpressionKind::Return(return_expression) => write!(f, "return {}", Printed(&return_expression.value, source)),
+
+        // Identifiers carry no name of their own, so they are printed as they were written.
+        ExpressionKind::Identifier => write!(f, "{}", source.lexeme(expression)),
+    }
+}
+
+/// An expression along with its source, which prints the expression as source code.
+struct Printed<'a>(&'a Expression, &'a Source);
+
+impl Display for Printed<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self::write_expression(f, self.0, self.1)
+    }
+}
+
+/// A pattern from the parser along with its source, which prints the pattern as source code.
+struct PrintedPattern<'a>(&'a MagcPattern, &'a Source);
+
+impl Display for PrintedPattern<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self::write_pattern(f, self.0, self.1)
+    }
+}
+
+/// Write expressions on indented lines of their own, like the children of a block.
+fn write_lines(f: &mut Formatter<'_>, expressions: &[Expression], source: &Source) -> Result {
+    for expression in expressions {
+        write!(f, "{}", self::indent(&Printed(expression, source).to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Indent every line of the given text by four spaces, ending each of them with a line break.
+fn indent(text: &str) -> String {
+    text.lines().map(|line| format!("    {}\n", line)).collect()
+}
+
+/// Write a pattern from the parser, which may contain malformed literals.
+fn write_pattern(f: &mut Formatter<'_>, pattern: &MagcPattern, source: &Source) -> Result {
+    match Pattern::from_checked(pattern.clone(), source) {
+        Ok(pattern) => write!(f, "{}", pattern),
+        Err(_)      => write!(f, "<invalid pattern>"),
+    }
+}
+
+/// Write the operand of an infix expression, adding parentheses if it would be parsed differently without them.
+fn write_operand(f: &mut Formatter<'_>, operand: &Expression, source: &Source, parent: Option<Operator>, right: bool) -> Result {
+    let needs_parentheses = match (&operand.kind, parent) {
+        (ExpressionKind::Infix(infix), Some(parent)) => match Operator::from_token(&infix.operator.kind) {
+            Some(operator) if right => operator.precedence() <= parent.precedence(),
+            Some(operator)          => operator.precedence() < parent.precedence(),
+            None                    => true,
+        },
+
+        (ExpressionKind::Infix(_), None) => true,
+        _                                => false,
+    };
+
+    if needs_parentheses {
+        write!(f, "(")?;
+        self::write_expression(f, operand, source)?;
+        write!(f, ")")
+    } else {
+        self::write_expression(f, operand, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::Interpreter;
+    use crate::interpreter::loader;
+    use crate::types::{ErrorValue, ListPattern, PairPattern, RestPattern, ValuePattern, VariablePattern};
+    use super::*;
+
+    fn int(n: i64) -> Obj {
+        Obj::new(ObjKind::Int(n))
+    }
+
+    fn parse(source: &str) -> Obj {
+        let mut objs = loader::parse(source, "test").expect("source should parse");
+        assert_eq!(objs.len(), 1, "`{}` should be a single expression", source);
+
+        objs.remove(0)
+    }
+
+    fn evaluate(source: &str) -> Obj {
+        *Interpreter::new().evaluate(Box::new(self::parse(source)), None).expect("source should evaluate")
+    }
+
+    #[test]
+    fn strings_are_quoted_and_escaped() {
+        let string = Obj::new(ObjKind::String(String::from("say \"hi\"\n")));
+
+        assert_eq!(string.to_string(), r#""say \"hi\"\n""#);
+    }
+
+    #[test]
+    fn lists_print_their_items() {
+        assert_eq!(Obj::list(vec![]).to_string(), "[]");
+        assert_eq!(Obj::list(vec![int(1)]).to_string(), "[1]");
+        assert_eq!(Obj::list(vec![int(1), int(2), int(3)]).to_string(), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn patterns_print_in_mag_syntax() {
+        let pattern = Pattern::List(ListPattern {
+            child: Some(Box::new(Pattern::Pair(PairPattern {
+                left:  Box::new(Pattern::Variable(VariablePattern {
+                    name:    Some(String::from("head")),
+                    type_id: Some(String::from("Int")),
+                })),
+                right: Box::new(Pattern::Rest(RestPattern { name: Some(String::from("tail")) })),
+            }))),
+        });
+
+        assert_eq!(pattern.to_string(), "[head Int, ...tail]");
+        assert_eq!(Pattern::Value(ValuePattern { obj: Box::new(int(4)) }).to_string(), "4");
+    }
+
+    #[test]
+    fn printed_expressions_parse_back_to_the_same_expression() {
+        let sources = [
+            "if n > 0 then 1 else 2",
+            "if n <= 0 then 1",
+            "n - 1 * 2",
+            "n != uint(3)",
+            "print(1)",
+            "-n + 1",
+            "!done",
+            "var total = n + 1",
+            "return n * 2",
+            "do\n    print(1)\n    print(2)\nend",
+            "def twice(n Int) n * 2",
+            "def report(n Int)\n    print(n)\n    n\nend",
+            "if n > 0 then\n    print(n)\nelse\n    print(0)\n    0\nend",
+            "match n\n    case 0 then \"zero\"\n    case 1 then\n        print(n)\n        \"one\"\n    end\n    else \"many\"\nend",
+        ];
+
+        for source in sources {
+            let printed = self::parse(source).to_string();
+
+            assert_eq!(printed, source);
+            assert_eq!(self::parse(&printed).to_string(), printed);
+        }
+    }
+
+    #[test]
+    fn printed_values_evaluate_to_an_equal_value() {
+        let values = [
+            int(42),
+            Obj::new(ObjKind::UInt(7)),
+            Obj::new(ObjKind::Float(String::from("1.5"))),
+            Obj::new(ObjKind::Boolean(true)),
+            Obj::new(ObjKind::String(String::from("hello"))),
+        ];
+
+        for value in values {
+            assert_eq!(self::evaluate(&value.to_string()).kind, value.kind);
+        }
+
+        let error = Obj::new(ObjKind::Error(ErrorValue::new(
+            String::from("NotFound"),
+            String::from("no such user"),
+            Box::new(int(7)),
+        )));
+
+        assert_eq!(error.to_string(), r#"error("NotFound", "no such user", 7)"#);
+        assert_eq!(self::evaluate(&error.to_string()).kind, error.kind);
+
+        let list = Obj::list(vec![int(1), int(2), int(3)]);
+        assert_eq!(self::evaluate(&list.to_string()).to_string(), list.to_string());
+    }
+
+    #[test]
+    fn computed_floats_print_as_floats() {
+        let sum = self::evaluate("1.5 + 0.5");
+
+        assert_eq!(sum.to_string(), "2.0");
+        assert_eq!(self::evaluate(&sum.to_string()).kind, sum.kind);
+
+        // Floats built by embedders may come without a decimal point.
+        assert_eq!(Obj::new(ObjKind::Float(String::from("2"))).to_string(), "2.0");
+    }
+
+    #[test]
+    fn unsigned_integers_print_as_constructor_calls() {
+        assert_eq!(Obj::new(ObjKind::UInt(5)).to_string(), "uint(5)");
+        assert_eq!(int(5).to_string(), "5");
+    }
+}
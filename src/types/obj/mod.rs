@@ -1,30 +1,34 @@
 mod arithmetic;
+mod display;
+pub(crate) mod nesting;
+mod operator;
+mod source;
 
 pub use self::arithmetic::*;
+pub use self::nesting::MAX_NESTING;
+pub use self::operator::Operator;
+pub use self::source::Source;
 
-use std::ops::{Add, Sub, Mul, Div};
 use std::cmp::PartialEq;
+use std::hash::{Hash, Hasher};
 use uuid::Uuid;
 
 use magc::type_system::Typed;
-use magc::types::*;
 
 use crate::interpreter::InterpreterError;
-use crate::types::Multimethod;
+use crate::types::{Assignment, Definition, ErrorValue, Import, Match, Module, Multimethod, Sequence, Throw, Try};
 
 use magc::types::{
     Expression,
     ExpressionKind,
-    Block,
-    Call,
-    Conditional,
-    Method,
-    Infix,
-    Prefix,
     Literal,
 };
 
-use crate::types::Pattern;
+use crate::types::{
+    Pattern,
+    PairPattern,
+    ValuePattern,
+};
 
 #[derive(Debug, Clone, Eq)]
 pub struct Obj {
@@ -40,27 +44,63 @@ impl Obj {
         }
     }
 
-    pub fn is_truthy(&self) -> bool {
-        match &self.kind {
-            ObjKind::Boolean(true) => true,
-            _                      => false,
-        }
-    }
-}
+    /// Create a list containing the given items.
+    ///
+    /// Lists with more than one item store them as a chain of pair patterns,
+    /// just like list expressions coming from the parser.
+    pub fn list(items: Vec<Obj>) -> Self {
+        let mut patterns = items
+            .into_iter()
+            .map(|obj| Pattern::Value(ValuePattern { obj: Box::new(obj) }))
+            .rev();
 
-impl std::fmt::Display for Obj {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string = match &self.kind {
-            ObjKind::Int(int)     => write!(f, "{}", int),
-            ObjKind::UInt(uint)   => write!(f, "{}", uint),
-            ObjKind::Float(float) => write!(f, "{}", float),
-            ObjKind::Boolean(boolean) => write!(f, "{:?}", boolean),
-            ObjKind::Nothing => write!(f, "nothing"),
+        let child = match patterns.next() {
+            Some(last) => {
+                let chain = patterns.fold(last, |right, left| Pattern::Pair(PairPattern {
+                    left:  Box::new(left),
+                    right: Box::new(right),
+                }));
 
-            _ => write!(f, "_"),
+                match chain {
+                    Pattern::Value(ValuePattern { obj }) => Some(obj),
+                    pattern                              => Some(Box::new(Obj::new(ObjKind::Pattern(pattern)))),
+                }
+            },
+
+            None => None,
         };
 
-        Ok(())
+        Obj::new(ObjKind::List(child))
+    }
+
+    /// Return the items of this object if it is a list.
+    pub fn list_items(&self) -> Option<Vec<Obj>> {
+        match &self.kind {
+            ObjKind::List(None)        => Some(vec![]),
+            ObjKind::List(Some(child)) => {
+                match &child.kind {
+                    ObjKind::Pattern(pattern @ Pattern::Pair(_)) => Some(
+                        pattern
+                            .clone()
+                            .flatten()
+                            .into_iter()
+                            .map(|item| match item {
+                                Pattern::Value(ValuePattern { obj }) => *obj,
+                                pattern                              => Obj::new(ObjKind::Pattern(pattern)),
+                            })
+                            .collect()
+                    ),
+
+                    _ => Some(vec![*child.clone()]),
+                }
+            },
+
+            _ => None,
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        matches!(self.kind, ObjKind::Boolean(true))
     }
 }
 
@@ -70,6 +110,25 @@ impl PartialEq for Obj {
     }
 }
 
+/// Objects are hashed by their kind and, for scalar values, the value itself.
+///
+/// Compound objects like multimethods or patterns only hash their kind, which keeps
+/// equal objects hashing equally without requiring every nested type to be hashable.
+impl Hash for Obj {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(&self.kind).hash(state);
+
+        match &self.kind {
+            ObjKind::Type(value) | ObjKind::Float(value) | ObjKind::String(value) => value.hash(state),
+            ObjKind::Int(value)     => value.hash(state),
+            ObjKind::UInt(value)    => value.hash(state),
+            ObjKind::Boolean(value) => value.hash(state),
+            ObjKind::Error(error)   => error.hash(state),
+            _                       => (),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ObjKind {
     /// An instance of a multimethod which is able to handle method calls.
@@ -91,8 +150,26 @@ pub enum ObjKind {
     Nothing,
     /// A list of objects enclosed in brackets.
     List(Option<Box<Obj>>),
-    /// A type which represents a Mag expression.
-    Expression(Expression),
+    /// A type which represents a Mag expression, along with the source code it was parsed from.
+    Expression(Expression, Source),
+    /// A `match` expression with a value and a list of arms.
+    Match(Match),
+    /// A `let` expression defining the variables of a pattern.
+    Definition(Definition),
+    /// An expression assigning a new value to an existing variable.
+    Assignment(Assignment),
+    /// A list of expressions sharing a new scope.
+    Sequence(Sequence),
+    /// An evaluated module whose exports can be accessed by qualified names.
+    Module(Module),
+    /// An `import` expression which makes the exports of a module available.
+    Import(Import),
+    /// An error value with a kind, a message and a payload.
+    Error(ErrorValue),
+    /// A `throw` expression which raises an error.
+    Throw(Throw),
+    /// A `try` expression which catches errors raised by its body.
+    Try(Try),
 }
 /*
     /// An `if` expression running different branches of code based on a given condition.
@@ -121,47 +198,89 @@ pub enum ObjKind {
     Identifier,
 */
 
-impl From<Expression> for Obj {
-    fn from(expression: Expression) -> Self {
+impl Obj {
+    /// Convert an expression from the parser into a runtime object.
+    ///
+    /// Literals are parsed into values, which fails if their text in the source is malformed.
+    /// Expressions nested more than `MAX_NESTING` levels deep are rejected.
+    pub fn from_expression(expression: Expression, source: &Source) -> Result<Self, InterpreterError> {
+        nesting::check_expression(&expression, 0)?;
+
+        Obj::from_checked(expression, source)
+    }
+
+    /// Convert an expression whose nesting has already been checked.
+    pub(crate) fn from_checked(expression: Expression, source: &Source) -> Result<Self, InterpreterError> {
         let kind = match expression.kind {
             ExpressionKind::List(optional_expr) => {
                 if let Some(inner_expr) = optional_expr {
-                    ObjKind::List(Some(Box::new(Obj::from(*inner_expr))))
+                    ObjKind::List(Some(Box::new(Obj::from_checked(*inner_expr, source)?)))
                 } else {
                     ObjKind::List(None)
                 }
             },
 
-            ExpressionKind::Pattern(pattern) => ObjKind::Pattern(Pattern::from(pattern)),
+            ExpressionKind::Pattern(pattern) => ObjKind::Pattern(Pattern::from_checked(pattern, source)?),
             ExpressionKind::Type(type_id)    => ObjKind::Type(type_id),
 
-            ExpressionKind::Literal(literal) => {
+            ExpressionKind::Literal(ref literal) => {
+                let lexeme = source.lexeme(&expression);
+
                 match literal {
-                    Literal::Int     => ObjKind::Int(expression.lexeme.parse::<i64>().unwrap()),
-                    Literal::Float   => ObjKind::Float(expression.lexeme),
-                    Literal::String  => ObjKind::String(expression.lexeme),
-                    Literal::Boolean => ObjKind::Boolean(expression.lexeme.parse::<bool>().unwrap()),
+                    Literal::Int     => ObjKind::Int(self::parse_literal(lexeme, "Int")?),
+                    Literal::Float   => {
+                        self::parse_literal::<f64>(lexeme.clone(), "Float")?;
+
+                        ObjKind::Float(lexeme)
+                    },
+                    Literal::String  => ObjKind::String(self::parse_string(lexeme)?),
+                    Literal::Boolean => ObjKind::Boolean(self::parse_literal(lexeme, "Boolean")?),
+                    Literal::Nothing => ObjKind::Nothing,
                 }
             },
 
-            _ => ObjKind::Expression(expression),
+            _ => ObjKind::Expression(expression, source.clone()),
         };
 
-        Obj::new(kind)
+        Ok(Obj::new(kind))
+    }
+}
+
+/// Remove the quotes around a string literal.
+fn parse_string(lexeme: String) -> Result<String, InterpreterError> {
+    match lexeme.strip_prefix('"').and_then(|lexeme| lexeme.strip_suffix('"')) {
+        Some(string) => Ok(string.to_string()),
+
+        None => Err(InterpreterError::InvalidLiteral {
+            expected: String::from("String"),
+            lexeme,
+        }),
     }
 }
 
+fn parse_literal<T: std::str::FromStr>(lexeme: String, expected: &str) -> Result<T, InterpreterError> {
+    lexeme.parse::<T>().map_err(|_| InterpreterError::InvalidLiteral {
+        expected: expected.to_string(),
+        lexeme,
+    })
+}
+
 impl Typed for Obj {
     fn get_type(&self) -> Option<String> {
         Some(match self.kind.clone() {
             ObjKind::Multimethod(_) => String::from("Multimethod"),
             ObjKind::Pattern(pattern)     => {
                 match pattern {
-                    Pattern::Field(_)    => String::from("FieldPattern"),
-                    Pattern::Pair(_)     => String::from("PairPattern"),
-                    Pattern::Tuple(_)    => String::from("TuplePattern"),
-                    Pattern::Value(_)    => String::from("ValuePattern"),
-                    Pattern::Variable(_) => String::from("VariablePattern"),
+                    Pattern::Alternative(_) => String::from("AlternativePattern"),
+                    Pattern::Field(_)       => String::from("FieldPattern"),
+                    Pattern::List(_)        => String::from("ListPattern"),
+                    Pattern::Named(_)       => String::from("NamedPattern"),
+                    Pattern::Pair(_)        => String::from("PairPattern"),
+                    Pattern::Range(_)       => String::from("RangePattern"),
+                    Pattern::Rest(_)        => String::from("RestPattern"),
+                    Pattern::Tuple(_)       => String::from("TuplePattern"),
+                    Pattern::Value(_)       => String::from("ValuePattern"),
+                    Pattern::Variable(_)    => String::from("VariablePattern"),
                 }
             },
             ObjKind::Int(_)         => String::from("Int"),
@@ -171,7 +290,17 @@ impl Typed for Obj {
             ObjKind::Boolean(_)     => String::from("Boolean"),
             ObjKind::List(_)        => String::from("List"),
 
-            ObjKind::Expression(expression)  => return expression.get_type(),
+            ObjKind::Match(_)       => String::from("MatchExpression"),
+            ObjKind::Definition(_)  => String::from("DefinitionExpression"),
+            ObjKind::Assignment(_)  => String::from("AssignmentExpression"),
+            ObjKind::Sequence(_)    => String::from("SequenceExpression"),
+            ObjKind::Import(_)      => String::from("ImportExpression"),
+            ObjKind::Module(_)      => String::from("Module"),
+            ObjKind::Throw(_)       => String::from("ThrowExpression"),
+            ObjKind::Try(_)         => String::from("TryExpression"),
+            ObjKind::Error(_)       => String::from("Error"),
+
+            ObjKind::Expression(expression, _) => return expression.get_type(),
             ObjKind::Type(type_id)           => type_id,
 
             ObjKind::Nothing => String::from("Nothing"),
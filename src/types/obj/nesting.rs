@@ -0,0 +1,150 @@
+//! A limit on how deeply expressions and patterns from the parser may be nested.
+//!
+//! Converting, evaluating and printing a tree recurses once for each level of nesting,
+//! so a deep enough tree would overflow the stack. Trees from the parser are checked
+//! before they are converted, which itself recurses at most `MAX_NESTING` levels.
+//!
+//! A tree is checked once where it enters the interpreter, which is `loader::parse`,
+//! `Obj::from_expression`, `Pattern::from_magc` or `Interpreter::evaluate_expr`. Its
+//! subtrees are converted and evaluated later without being checked again.
+//!
+//! Only trees coming from the parser are checked. Runtime objects and patterns which an
+//! embedder builds directly are not, and neither is dropping a tree or parsing it in `magc`.
+
+use magc::types::{Expression, ExpressionKind};
+use magc::types::Pattern as MagcPattern;
+
+use crate::interpreter::InterpreterError;
+
+/// How many levels of expressions and patterns from the parser may be nested inside each other.
+pub const MAX_NESTING: usize = 256;
+
+/// Fail with `TooDeeplyNested` if the expression is nested more than `MAX_NESTING` levels deep.
+pub fn check_expression(expression: &Expression, depth: usize) -> Result<(), InterpreterError> {
+    if depth >= MAX_NESTING {
+        return Err(InterpreterError::TooDeeplyNested)
+    }
+
+    let depth = depth + 1;
+
+    match &expression.kind {
+        ExpressionKind::List(Some(child)) => self::check_expression(child, depth),
+        ExpressionKind::Pattern(pattern)  => self::check_pattern(pattern, depth),
+
+        ExpressionKind::Call(call) => self::check_signature(&call.signature, depth),
+
+        ExpressionKind::Method(method) => {
+            self::check_signature(&method.signature, depth)?;
+            self::check_expression(&method.body, depth)
+        },
+
+        ExpressionKind::Conditional(conditional) => {
+            self::check_expression(&conditional.condition, depth)?;
+            self::check_expression(&conditional.then_arm, depth)?;
+
+            match &conditional.else_arm {
+                Some(else_arm) => self::check_expression(else_arm, depth),
+                None           => Ok(()),
+            }
+        },
+
+        ExpressionKind::Infix(infix) => {
+            self::check_expression(&infix.left, depth)?;
+            self::check_expression(&infix.right, depth)
+        },
+
+        ExpressionKind::Prefix(prefix)  => self::check_expression(&prefix.operand, depth),
+        ExpressionKind::Var(var)        => self::check_expression(&var.value, depth),
+        ExpressionKind::Return(value)   => self::check_expression(&value.value, depth),
+
+        ExpressionKind::Block(block) => block.children
+            .iter()
+            .chain(block.environment.values())
+            .try_for_each(|child| self::check_expression(child, depth)),
+
+        ExpressionKind::Match(match_expression) => {
+            self::check_expression(&match_expression.subject, depth)?;
+
+            for arm in &match_expression.arms {
+                self::check_pattern(&arm.pattern, depth)?;
+                self::check_expression(&arm.body, depth)?;
+            }
+
+            match &match_expression.else_arm {
+                Some(else_arm) => self::check_expression(else_arm, depth),
+                None           => Ok(()),
+            }
+        },
+
+        ExpressionKind::List(None)
+        | ExpressionKind::Literal(_)
+        | ExpressionKind::Type(_)
+        | ExpressionKind::Identifier => Ok(()),
+    }
+}
+
+/// Fail with `TooDeeplyNested` if the pattern is nested more than `MAX_NESTING` levels deep.
+pub fn check_pattern(pattern: &MagcPattern, depth: usize) -> Result<(), InterpreterError> {
+    if depth >= MAX_NESTING {
+        return Err(InterpreterError::TooDeeplyNested)
+    }
+
+    let depth = depth + 1;
+
+    match pattern {
+        MagcPattern::Record(field_pattern) => self::check_pattern(&field_pattern.value, depth),
+        MagcPattern::Tuple(tuple_pattern) => self::check_pattern(&tuple_pattern.child, depth),
+        MagcPattern::Value(value_pattern) => self::check_expression(&value_pattern.expression, depth),
+
+        MagcPattern::Pair(pair_pattern) => {
+            self::check_pattern(&pair_pattern.left, depth)?;
+            self::check_pattern(&pair_pattern.right, depth)
+        },
+
+        MagcPattern::Variable(_) => Ok(()),
+    }
+}
+
+fn check_signature(signature: &Option<MagcPattern>, depth: usize) -> Result<(), InterpreterError> {
+    match signature {
+        Some(pattern) => self::check_pattern(pattern, depth),
+        None          => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magc::types::{Block, Literal, Prefix};
+
+    use super::*;
+
+    /// Nest a literal inside the given number of list expressions.
+    fn lists(levels: usize) -> Expression {
+        let expression = |kind| Expression { kind, start_pos: 0, end_pos: 0 };
+
+        (0..levels).fold(expression(ExpressionKind::Literal(Literal::Int)), |child, _| {
+            expression(ExpressionKind::List(Some(Box::new(child))))
+        })
+    }
+
+    #[test]
+    fn every_kind_with_children_counts_towards_the_limit() {
+        let expression = |kind| Expression { kind, start_pos: 0, end_pos: 0 };
+
+        let operator = magc::types::Token { kind: magc::types::TokenKind::Minus, start_pos: 0, end_pos: 0, line: 1 };
+        let prefix = |operand| expression(ExpressionKind::Prefix(Prefix { operator: operator.clone(), operand: Box::new(operand) }));
+        let block = |child| expression(ExpressionKind::Block(Block { environment: Default::default(), children: vec![child] }));
+
+        // Alternate between prefix and block expressions, so both have to be counted.
+        let nested = |pairs: usize| (0..pairs).fold(lists(0), |child, _| block(prefix(child)));
+
+        assert_eq!(check_expression(&nested(MAX_NESTING / 2 - 1), 0), Ok(()));
+        assert_eq!(check_expression(&nested(MAX_NESTING / 2), 0), Err(InterpreterError::TooDeeplyNested));
+    }
+
+    #[test]
+    fn trees_up_to_the_limit_are_accepted() {
+        assert_eq!(check_expression(&lists(MAX_NESTING - 1), 0), Ok(()));
+        assert_eq!(check_expression(&lists(MAX_NESTING), 0), Err(InterpreterError::TooDeeplyNested));
+    }
+}
@@ -0,0 +1,158 @@
+use std::cmp::Ordering;
+
+use magc::parser::{PREC_COMPARISON, PREC_EQUALITY, PREC_PRODUCT, PREC_TERM};
+use magc::types::TokenKind;
+
+use super::*;
+
+use crate::interpreter::InterpreterError;
+
+/// A binary operator of an infix expression, like `+` or `<=`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    /// Return the operator written with the given token, if it is a binary operator.
+    pub fn from_token(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::Plus         => Some(Self::Add),
+            TokenKind::Minus        => Some(Self::Subtract),
+            TokenKind::Star         => Some(Self::Multiply),
+            TokenKind::Slash        => Some(Self::Divide),
+            TokenKind::EqualEqual   => Some(Self::Equal),
+            TokenKind::BangEqual    => Some(Self::NotEqual),
+            TokenKind::Smaller      => Some(Self::Less),
+            TokenKind::SmallerEqual => Some(Self::LessOrEqual),
+            TokenKind::Greater      => Some(Self::Greater),
+            TokenKind::GreaterEqual => Some(Self::GreaterOrEqual),
+
+            _ => None,
+        }
+    }
+
+    /// Return the operator as it is written in source code.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add            => "+",
+            Self::Subtract       => "-",
+            Self::Multiply       => "*",
+            Self::Divide         => "/",
+            Self::Equal          => "==",
+            Self::NotEqual       => "!=",
+            Self::Less           => "<",
+            Self::LessOrEqual    => "<=",
+            Self::Greater        => ">",
+            Self::GreaterOrEqual => ">=",
+        }
+    }
+
+    /// Return how tightly this operator binds, which is the precedence `magc` parses it with.
+    pub fn precedence(&self) -> usize {
+        match self {
+            Self::Add | Self::Subtract    => PREC_TERM,
+            Self::Multiply | Self::Divide => PREC_PRODUCT,
+            Self::Equal | Self::NotEqual  => PREC_EQUALITY,
+
+            Self::Less | Self::LessOrEqual | Self::Greater | Self::GreaterOrEqual => PREC_COMPARISON,
+        }
+    }
+
+    /// Apply this operator to two evaluated operands.
+    ///
+    /// Numbers and characters are compared with [`compare`], so `1 == 1.0` holds.
+    /// Any other values are equal if they have the same contents.
+    pub fn apply(&self, left: Obj, right: Obj) -> Result<Obj, InterpreterError> {
+        let result = match self {
+            Self::Add      => return left + right,
+            Self::Subtract => return left - right,
+            Self::Multiply => return left * right,
+            Self::Divide   => return left / right,
+
+            Self::Equal    => self::equals(&left, &right),
+            Self::NotEqual => !self::equals(&left, &right),
+
+            Self::Less           => compare(&left, &right)? == Ordering::Less,
+            Self::LessOrEqual    => compare(&left, &right)? != Ordering::Greater,
+            Self::Greater        => compare(&left, &right)? == Ordering::Greater,
+            Self::GreaterOrEqual => compare(&left, &right)? != Ordering::Less,
+        };
+
+        Ok(Obj::new(ObjKind::Boolean(result)))
+    }
+}
+
+fn equals(left: &Obj, right: &Obj) -> bool {
+    match compare(left, right) {
+        Ok(ordering) => ordering == Ordering::Equal,
+        Err(_)       => left.kind == right.kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> Obj {
+        Obj::new(ObjKind::Int(n))
+    }
+
+    fn boolean(value: bool) -> Obj {
+        Obj::new(ObjKind::Boolean(value))
+    }
+
+    #[test]
+    fn tokens_map_to_their_operators() {
+        let operators = [
+            (TokenKind::Plus,         Operator::Add),
+            (TokenKind::Minus,        Operator::Subtract),
+            (TokenKind::Star,         Operator::Multiply),
+            (TokenKind::Slash,        Operator::Divide),
+            (TokenKind::EqualEqual,   Operator::Equal),
+            (TokenKind::BangEqual,    Operator::NotEqual),
+            (TokenKind::Smaller,      Operator::Less),
+            (TokenKind::SmallerEqual, Operator::LessOrEqual),
+            (TokenKind::Greater,      Operator::Greater),
+            (TokenKind::GreaterEqual, Operator::GreaterOrEqual),
+        ];
+
+        for (token, operator) in operators {
+            assert_eq!(Operator::from_token(&token), Some(operator));
+        }
+
+        assert_eq!(Operator::from_token(&TokenKind::Percent), None);
+    }
+
+    #[test]
+    fn comparisons_promote_numbers() {
+        let float = || Obj::new(ObjKind::Float(String::from("1.5")));
+
+        assert_eq!(Operator::Less.apply(int(1), float()).map(|obj| obj.kind), Ok(ObjKind::Boolean(true)));
+        assert_eq!(Operator::GreaterOrEqual.apply(int(1), float()).map(|obj| obj.kind), Ok(ObjKind::Boolean(false)));
+        assert_eq!(Operator::Equal.apply(int(1), Obj::new(ObjKind::UInt(1))).map(|obj| obj.kind), Ok(ObjKind::Boolean(true)));
+    }
+
+    #[test]
+    fn other_values_are_only_checked_for_equality() {
+        assert_eq!(Operator::Equal.apply(boolean(true), boolean(true)).map(|obj| obj.kind), Ok(ObjKind::Boolean(true)));
+        assert_eq!(Operator::NotEqual.apply(boolean(true), int(1)).map(|obj| obj.kind), Ok(ObjKind::Boolean(true)));
+
+        assert_eq!(
+            Operator::Less.apply(boolean(true), boolean(false)),
+            Err(InterpreterError::UnexpectedType {
+                expected: String::from("Int | UInt | Float | Character"),
+                found:    Some(String::from("Boolean")),
+            }),
+        );
+    }
+}
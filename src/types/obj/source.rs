@@ -0,0 +1,64 @@
+//! The source code expressions were parsed from.
+//!
+//! The syntax tree from `magc` does not keep any text, only the positions of each
+//! expression in the source, counted in graphemes. The text of literals and the
+//! operators of printed expressions are read from the source when they are needed.
+//!
+//! Guard clauses like `where n > 0` are not part of the grammar of `magc`, so the loader
+//! parses them separately and keeps them here, keyed by the position of their `def`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use magc::types::Expression;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The graphemes of a piece of source code, shared by every expression parsed from it.
+#[derive(Clone, Default, Eq)]
+pub struct Source {
+    graphemes: Arc<[String]>,
+    guards:    Arc<HashMap<usize, Expression>>,
+}
+
+impl Source {
+    pub fn new(text: &str) -> Self {
+        Self {
+            graphemes: text.graphemes(true).map(String::from).collect(),
+            guards:    Arc::default(),
+        }
+    }
+
+    /// Attach the guard clauses of the method definitions in this source, keyed by the position of their `def`.
+    pub fn with_guards(mut self, guards: HashMap<usize, Expression>) -> Self {
+        self.guards = Arc::new(guards);
+        self
+    }
+
+    /// Return the guard clause of a method definition parsed from this source, if it has one.
+    pub fn guard(&self, method: &Expression) -> Option<&Expression> {
+        self.guards.get(&method.start_pos)
+    }
+
+    /// Return the text between two positions, or an empty string if they are out of bounds.
+    pub fn text(&self, start: usize, end: usize) -> String {
+        self.graphemes.get(start..end).map(|graphemes| graphemes.concat()).unwrap_or_default()
+    }
+
+    /// Return the text an expression was parsed from.
+    pub fn lexeme(&self, expression: &Expression) -> String {
+        self.text(expression.start_pos, expression.end_pos)
+    }
+}
+
+impl PartialEq for Source {
+    fn eq(&self, other: &Self) -> bool {
+        (Arc::ptr_eq(&self.graphemes, &other.graphemes) || self.graphemes == other.graphemes)
+            && (Arc::ptr_eq(&self.guards, &other.guards) || self.guards == other.guards)
+    }
+}
+
+impl std::fmt::Debug for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Source({} graphemes)", self.graphemes.len())
+    }
+}
@@ -0,0 +1,52 @@
+use super::Pattern;
+use crate::interpreter::InterpreterError;
+
+/// A set of patterns separated by vertical bars, like `0 | 1` or `x Int | x Float`.
+///
+/// The pattern matches if any of its branches matches. Every branch has to bind the
+/// same set of variables, so the body can rely on them no matter which branch matched.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AlternativePattern {
+    branches: Vec<Pattern>,
+}
+
+impl AlternativePattern {
+    pub fn new(branches: Vec<Pattern>) -> Result<Self, InterpreterError> {
+        if let Some((first, others)) = branches.split_first() {
+            let expected = first.bound_names();
+
+            for branch in others {
+                let found = branch.bound_names();
+
+                if found != expected {
+                    return Err(InterpreterError::InconsistentBindings {
+                        expected: expected.into_iter().collect(),
+                        found:    found.into_iter().collect(),
+                    })
+                }
+            }
+        }
+
+        Ok(Self {
+            branches,
+        })
+    }
+
+    pub fn branches(&self) -> &[Pattern] {
+        &self.branches
+    }
+}
+
+impl std::fmt::Display for AlternativePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, branch) in self.branches.iter().enumerate() {
+            if index > 0 {
+                write!(f, " | ")?;
+            }
+
+            write!(f, "{}", branch)?;
+        }
+
+        Ok(())
+    }
+}
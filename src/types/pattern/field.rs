@@ -1,8 +1,23 @@
 use super::Pattern;
+use crate::types::Obj;
 
 /// A named pattern, like `repeats: 4` or `name: n String`.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FieldPattern {
-    pub name:  String,
-    pub value: Box<Pattern>,
-}
\ No newline at end of file
+    pub name:    String,
+    pub value:   Box<Pattern>,
+    /// A value bound to the inner pattern if the field is missing, like in `retries: n Int = 3`.
+    pub default: Option<Box<Obj>>,
+}
+
+impl std::fmt::Display for FieldPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)?;
+
+        if let Some(default) = &self.default {
+            write!(f, " = {}", default)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use super::Pattern;
+
+/// A pattern enclosed in brackets which matches the items of a list, like `[head, ...tail]`.
+///
+/// The optional child is a chain of pair patterns if there is more than one item,
+/// and an empty list pattern only matches empty lists. List literals in a signature are
+/// converted into list patterns.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ListPattern {
+    pub child: Option<Box<Pattern>>,
+}
+
+impl ListPattern {
+    /// Return the item patterns of this list.
+    pub fn items(&self) -> Vec<Pattern> {
+        match &self.child {
+            Some(child) => child.clone().flatten(),
+            None        => vec![],
+        }
+    }
+}
+
+impl std::fmt::Display for ListPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.child {
+            Some(child) => write!(f, "[{}]", child),
+            None        => write!(f, "[]"),
+        }
+    }
+}
@@ -1,70 +1,123 @@
 //! A runtime version of the pattern type from `magc` which adds support for pattern matching.
 
+mod alternative;
 mod field;
+mod list;
+mod named;
 mod pair;
+mod range;
+mod rest;
 mod tuple;
 mod value;
 mod variable;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use magc::types::{
+    Expression,
+    ExpressionKind,
+};
 use magc::types::Pattern as MagcPattern;
 use magc::Typed;
 
+pub use self::alternative::AlternativePattern;
 pub use self::field::FieldPattern;
+pub use self::list::ListPattern;
+pub use self::named::NamedPattern;
 pub use self::pair::PairPattern;
+pub use self::range::RangePattern;
+pub use self::rest::RestPattern;
 pub use self::tuple::TuplePattern;
 pub use self::value::ValuePattern;
 pub use self::variable::VariablePattern;
 
 use crate::types::{
     Obj,
+    ObjKind,
     Environment,
+    Source,
+};
+use crate::types::obj::nesting;
+use crate::interpreter::{
+    Interpreter,
+    InterpreterError,
 };
-use crate::interpreter::InterpreterError;
 
 pub type MatchResult = Result<Environment, InterpreterError>;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 /// A runtime version of the pattern type from `magc` which adds support for pattern matching.
 pub enum Pattern {
+    Alternative(AlternativePattern),
     Field(FieldPattern),
+    List(ListPattern),
+    Named(NamedPattern),
     Pair(PairPattern),
+    Range(RangePattern),
+    Rest(RestPattern),
     Tuple(TuplePattern),
     Value(ValuePattern),
     Variable(VariablePattern),
 }
 
-impl From<MagcPattern> for Pattern {
-    fn from(p: MagcPattern) -> Self {
-        match p {
-            MagcPattern::Field(field_pattern) => Self::Field(FieldPattern {
-                name: field_pattern.name,
-                value: Box::new(Pattern::from(*field_pattern.value)),
+impl Pattern {
+    /// Convert a pattern from the parser, rejecting patterns nested more than `MAX_NESTING` levels deep.
+    pub fn from_magc(p: MagcPattern, source: &Source) -> Result<Self, InterpreterError> {
+        nesting::check_pattern(&p, 0)?;
+
+        Pattern::from_checked(p, source)
+    }
+
+    /// Convert a pattern from the parser whose nesting has already been checked.
+    pub(crate) fn from_checked(p: MagcPattern, source: &Source) -> Result<Self, InterpreterError> {
+        Ok(match p {
+            MagcPattern::Record(field_pattern) => Self::Field(FieldPattern {
+                name:    field_pattern.name,
+                value:   Box::new(Pattern::from_checked(*field_pattern.value, source)?),
+                default: None,
             }),
 
             MagcPattern::Pair(pair_pattern) => Self::Pair(PairPattern {
-                left:  Box::new(Pattern::from(*pair_pattern.left)),
-                right: Box::new(Pattern::from(*pair_pattern.right)),
+                left:  Box::new(Pattern::from_checked(*pair_pattern.left, source)?),
+                right: Box::new(Pattern::from_checked(*pair_pattern.right, source)?),
             }),
 
             MagcPattern::Tuple(tuple_pattern) => Self::Tuple(TuplePattern {
-                child: Box::new(Pattern::from(*tuple_pattern.child)),
+                child: Box::new(Pattern::from_checked(*tuple_pattern.child, source)?),
             }),
 
-            MagcPattern::Value(value_pattern) => Self::Value(ValuePattern {
-                obj: Box::new(Obj::from(*value_pattern.expression)),
-            }),
+            MagcPattern::Value(value_pattern) => match value_pattern.expression.kind {
+                // List literals in patterns destructure the items of a list.
+                ExpressionKind::List(child) => Self::List(ListPattern {
+                    child: match child {
+                        Some(expression) => Some(Box::new(Pattern::from_expression(*expression, source)?)),
+                        None             => None,
+                    },
+                }),
+
+                _ => Self::Value(ValuePattern {
+                    obj: Box::new(Obj::from_checked(*value_pattern.expression, source)?),
+                }),
+            },
 
             MagcPattern::Variable(variable_pattern) => Self::Variable(VariablePattern {
                 name:    variable_pattern.name,
                 type_id: variable_pattern.type_id,
             }),
-        }
+        })
+    }
+
+    /// Convert an expression into a pattern, wrapping anything but patterns into a value pattern.
+    fn from_expression(expression: Expression, source: &Source) -> Result<Self, InterpreterError> {
+        Ok(match expression.kind {
+            ExpressionKind::Pattern(pattern) => Pattern::from_checked(pattern, source)?,
+
+            _ => Pattern::Value(ValuePattern {
+                obj: Box::new(Obj::from_checked(expression, source)?),
+            }),
+        })
     }
-}
 
-impl Pattern {
     /// Compare this pattern with another and return any destructured variables.
     ///
     /// This function recursively calls itself and the respective pattern methods
@@ -72,40 +125,268 @@ impl Pattern {
     /// variable matches any value pattern, for example.
     pub fn linearize(&self, other: Pattern) -> MatchResult {
         match self {
-            Pattern::Field(reference)    => self.linearize_field(reference.clone(), other),
-            Pattern::Tuple(reference)    => self.linearize_tuple(reference.clone(), other),
-            Pattern::Value(reference)    => self.linearize_value(reference.clone(), other),
-            Pattern::Variable(reference) => self.linearize_variable(reference.clone(), other),
-            Pattern::Pair(reference)     => self.linearize_pair(reference.clone(), other),
+            Pattern::Alternative(reference) => self.linearize_alternative(reference.clone(), other),
+            Pattern::Field(reference)       => self.linearize_field(reference.clone(), other),
+            Pattern::List(reference)        => self.linearize_list(reference.clone(), other),
+            Pattern::Named(reference)       => self.linearize_named(reference.clone(), other),
+            Pattern::Tuple(reference)       => self.linearize_tuple(reference.clone(), other),
+            Pattern::Value(reference)       => self.linearize_value(reference.clone(), other),
+            Pattern::Variable(reference)    => self.linearize_variable(reference.clone(), other),
+            Pattern::Pair(reference)        => self.linearize_pair(reference.clone(), other),
+            Pattern::Range(reference)       => self.linearize_range(reference.clone(), other),
+            Pattern::Rest(reference)        => self.linearize_rest(reference.clone(), other),
         }
     }
 
     pub fn matches_with(&self, other: Pattern) -> bool {
-        match self.linearize(other) {
-            Ok(_)  => true,
-            Err(_) => false,
+        self.linearize(other).is_ok()
+    }
+
+    /// Return whether this pattern can be left out of a call entirely.
+    ///
+    /// This is the case for fields with a default value, rest patterns and any combination of them.
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Pattern::Alternative(alternative) => alternative.branches().iter().any(|branch| branch.is_optional()),
+            Pattern::Field(field) => field.default.is_some(),
+            Pattern::Rest(_)      => true,
+            Pattern::Pair(pair)   => pair.left.is_optional() && pair.right.is_optional(),
+            Pattern::Tuple(tuple) => tuple.child.is_optional(),
+
+            _ => false,
+        }
+    }
+
+    /// Bind the default values of all optional fields in this pattern.
+    ///
+    /// Rest patterns are bound to an empty list.
+    pub fn defaults(&self) -> MatchResult {
+        match self {
+            Pattern::Alternative(alternative) => {
+                match alternative.branches().iter().find(|branch| branch.is_optional()) {
+                    Some(branch) => branch.defaults(),
+                    None         => Ok(Environment::empty()),
+                }
+            },
+
+            Pattern::Field(FieldPattern { value, default: Some(default), .. }) => {
+                value.linearize(Pattern::Value(ValuePattern { obj: default.clone() }))
+            },
+
+            Pattern::Rest(rest) => self::bind_rest(rest, vec![]),
+
+            Pattern::Pair(pair)   => pair.left.defaults()?.merge(pair.right.defaults()?),
+            Pattern::Tuple(tuple) => tuple.child.defaults(),
+
+            _ => Ok(Environment::empty()),
+        }
+    }
+
+    /// Evaluate the default values of all fields in this pattern in the given scope.
+    pub fn evaluate_defaults(self, interpreter: &mut Interpreter, optional_env: Option<Environment>) -> Result<Pattern, InterpreterError> {
+        Ok(match self {
+            Pattern::Field(FieldPattern { name, value, default }) => Pattern::Field(FieldPattern {
+                name,
+                value:   Box::new(value.evaluate_defaults(interpreter, optional_env.clone())?),
+                default: match default {
+                    Some(obj) => Some(interpreter.evaluate(obj, optional_env)?),
+                    None      => None,
+                },
+            }),
+
+            Pattern::Pair(PairPattern { left, right }) => Pattern::Pair(PairPattern {
+                left:  Box::new(left.evaluate_defaults(interpreter, optional_env.clone())?),
+                right: Box::new(right.evaluate_defaults(interpreter, optional_env)?),
+            }),
+
+            Pattern::Tuple(TuplePattern { child }) => Pattern::Tuple(TuplePattern {
+                child: Box::new(child.evaluate_defaults(interpreter, optional_env)?),
+            }),
+
+            Pattern::Named(NamedPattern { name, pattern }) => Pattern::Named(NamedPattern {
+                name,
+                pattern: Box::new(pattern.evaluate_defaults(interpreter, optional_env)?),
+            }),
+
+            Pattern::Alternative(alternative) => {
+                let mut evaluated = vec![];
+
+                for branch in alternative.branches() {
+                    evaluated.push(branch.clone().evaluate_defaults(interpreter, optional_env.clone())?);
+                }
+
+                Pattern::Alternative(AlternativePattern::new(evaluated)?)
+            },
+
+            pattern => pattern,
+        })
+    }
+
+    /// Split a chain of pair patterns into its items, like `a, b, c` into `[a, b, c]`.
+    pub fn flatten(self) -> Vec<Pattern> {
+        match self {
+            Pattern::Pair(PairPattern { left, right }) => {
+                let mut items = left.flatten();
+                items.extend(right.flatten());
+
+                items
+            },
+
+            pattern => vec![pattern],
+        }
+    }
+
+    /// Return the names of all variables which are bound when this pattern matches.
+    pub fn bound_names(&self) -> BTreeSet<String> {
+        let mut names = vec![];
+        self.collect_names(&mut names);
+
+        names.into_iter().collect()
+    }
+
+    /// Make sure that no variable name is bound more than once by this pattern.
+    ///
+    /// The branches of an alternative pattern are only counted once, since they bind the same names.
+    pub fn check_bindings(&self) -> Result<(), InterpreterError> {
+        let mut names = vec![];
+        self.collect_names(&mut names);
+
+        let mut seen = BTreeSet::new();
+
+        for name in names {
+            if !seen.insert(name.clone()) {
+                return Err(InterpreterError::DuplicateBinding { name })
+            }
+        }
+
+        Ok(())
+    }
+
+    fn collect_names(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Field(field) => field.value.collect_names(names),
+            Pattern::Tuple(tuple) => tuple.child.collect_names(names),
+
+            Pattern::Alternative(alternative) => {
+                if let Some(branch) = alternative.branches().first() {
+                    branch.collect_names(names);
+                }
+            },
+
+            Pattern::List(list) => {
+                if let Some(child) = &list.child {
+                    child.collect_names(names);
+                }
+            },
+
+            Pattern::Named(named) => {
+                names.push(named.name.clone());
+                named.pattern.collect_names(names);
+            },
+
+            Pattern::Pair(pair) => {
+                pair.left.collect_names(names);
+                pair.right.collect_names(names);
+            },
+
+            Pattern::Rest(RestPattern { name: Some(name) })             => names.push(name.clone()),
+            Pattern::Variable(VariablePattern { name: Some(name), .. }) => names.push(name.clone()),
+
+            _ => (),
+        }
+    }
+
+    /// Return whether this pattern accepts a variable number of items.
+    pub fn has_rest(&self) -> bool {
+        match self {
+            Pattern::Alternative(alternative) => alternative.branches().iter().any(|branch| branch.has_rest()),
+            Pattern::Rest(_)      => true,
+            Pattern::Field(field) => field.value.has_rest(),
+            Pattern::List(list)   => list.child.as_ref().is_some_and(|child| child.has_rest()),
+            Pattern::Named(named) => named.pattern.has_rest(),
+            Pattern::Pair(pair)   => pair.left.has_rest() || pair.right.has_rest(),
+            Pattern::Tuple(tuple) => tuple.child.has_rest(),
+
+            _ => false,
         }
     }
 
     pub fn get_precedence(&self) -> usize {
+        // Variadic patterns should only be used if no pattern with a fixed number of items matches.
+        if self.has_rest() { return 0 }
+
         match self {
-            Pattern::Value(_) => 2,
-            _                 => 1,
+            // An alternative is only as specific as its most general branch.
+            Pattern::Alternative(alternative) => {
+                alternative.branches()
+                    .iter()
+                    .map(|branch| branch.get_precedence())
+                    .min()
+                    .unwrap_or(0)
+            },
+
+            Pattern::Named(named) => named.pattern.get_precedence(),
+            Pattern::Value(_)     => 3,
+            Pattern::List(_)      => 3,
+            Pattern::Range(_)     => 2,
+            _                     => 1,
+        }
+    }
+
+    fn linearize_alternative(&self, reference: AlternativePattern, other: Pattern) -> MatchResult {
+        for branch in reference.branches() {
+            if let Ok(env) = branch.linearize(other.clone()) {
+                return Ok(env)
+            }
         }
+
+        Err(InterpreterError::NoMatch)
     }
 
     fn linearize_field(&self, reference: FieldPattern, other: Pattern) -> MatchResult {
-        if let Pattern::Field(given) = other {
+        if let Pattern::Field(given) = self::destructure(other) {
             if given.name != reference.name { return Err(InterpreterError::NoMatch) }
 
-            given.value.linearize(*reference.value)
+            reference.value.linearize(*given.value)
+        } else {
+            Err(InterpreterError::NoMatch)
+        }
+    }
+
+    fn linearize_list(&self, reference: ListPattern, other: Pattern) -> MatchResult {
+        let given_items = match other {
+            Pattern::List(list) => list.items(),
+
+            // A list value coming from a variable or expression.
+            Pattern::Value(ValuePattern { obj }) => match obj.list_items() {
+                Some(items) => items
+                    .into_iter()
+                    .map(|obj| Pattern::Value(ValuePattern { obj: Box::new(obj) }))
+                    .collect(),
+
+                None => return Err(InterpreterError::NoMatch),
+            },
+
+            _ => return Err(InterpreterError::NoMatch),
+        };
+
+        self::linearize_sequence(&reference.items(), &given_items)
+    }
+
+    fn linearize_range(&self, reference: RangePattern, other: Pattern) -> MatchResult {
+        if let Pattern::Value(ValuePattern { obj }) = other {
+            if reference.contains(&obj) {
+                Ok(Environment::empty())
+            } else {
+                Err(InterpreterError::NoMatch)
+            }
         } else {
             Err(InterpreterError::NoMatch)
         }
     }
 
     fn linearize_tuple(&self, reference: TuplePattern, other: Pattern) -> MatchResult {
-        if let Pattern::Tuple(TuplePattern { child: other_pattern }) = other {
+        if let Pattern::Tuple(TuplePattern { child: other_pattern }) = self::destructure(other) {
             reference.child.linearize(*other_pattern)
         } else {
             Err(InterpreterError::NoMatch)
@@ -128,36 +409,486 @@ impl Pattern {
         let mut variables = HashMap::new();
 
         if let Some(name) = reference.name {
+            // A chain of pairs is a list of arguments rather than a single value.
+            if let Pattern::Pair(_) | Pattern::Rest(_) = other {
+                return Err(InterpreterError::NoMatch)
+            }
+
             // Extract value into environment and check types if there is an annotation.
-            if let Pattern::Value(ValuePattern { obj }) = other {
-                if let Some(type_id) = reference.type_id {
-                    if obj.get_type() == Some(type_id) {
-                        variables.insert(VariablePattern { name: Some(name), type_id: None }, obj);
-                    } else {
-                        return Err(InterpreterError::NoMatch)
-                    }
-                } else {
-                    variables.insert(VariablePattern { name: Some(name), type_id: None }, obj);
+            let obj = self::into_obj(other);
+
+            if let Some(type_id) = reference.type_id {
+                if obj.get_type() != Some(type_id) {
+                    return Err(InterpreterError::NoMatch)
                 }
-            } else {
-                // TODO: add proper error handling here!
-                return Err(InterpreterError::NoMatch)
             }
+
+            variables.insert(VariablePattern::from_name(name), Box::new(obj));
         }
-        
+
         Ok(Environment::from(variables))
     }
 
+    fn linearize_named(&self, reference: NamedPattern, other: Pattern) -> MatchResult {
+        let parts = reference.pattern.linearize(other.clone())?;
+        let whole = self::into_obj(other);
+
+        let mut variables = HashMap::new();
+        variables.insert(VariablePattern::from_name(reference.name), Box::new(whole));
+
+        Environment::from(variables).merge(parts)
+    }
+
     fn linearize_pair(&self, reference: PairPattern, other: Pattern) -> MatchResult {
-        if let Pattern::Pair(PairPattern { left, right }) = other {
-            let mut left_map = reference.left.linearize(*left)?;
-            let right_map    = reference.right.linearize(*right)?;
+        self::linearize_sequence(
+            &Pattern::Pair(reference).flatten(),
+            &other.flatten(),
+        )
+    }
 
-            left_map.extend(right_map);
+    fn linearize_rest(&self, reference: RestPattern, other: Pattern) -> MatchResult {
+        self::bind_rest(&reference, other.flatten())
+    }
+}
 
-            Ok(left_map)
-        } else {
-            Err(InterpreterError::NoMatch)
+/// Match the items of two flattened pair chains.
+///
+/// Positional items are matched one by one and have to come before any fields,
+/// which are matched by name regardless of their order.
+fn linearize_sequence(reference: &[Pattern], given: &[Pattern]) -> MatchResult {
+    let (reference_positional, reference_fields) = self::split_fields(reference)?;
+    let (given_positional, given_fields)         = self::split_fields(given)?;
+
+    let positional_map = self::linearize_positional(&reference_positional, &given_positional)?;
+    let field_map      = self::linearize_fields(&reference_fields, given_fields)?;
+
+    positional_map.merge(field_map)
+}
+
+/// Separate the leading positional items of a sequence from the trailing fields.
+fn split_fields(items: &[Pattern]) -> Result<(Vec<Pattern>, Vec<FieldPattern>), InterpreterError> {
+    let mut positional = vec![];
+    let mut fields     = vec![];
+
+    for item in items {
+        match item {
+            Pattern::Field(field) => fields.push(field.clone()),
+
+            item => {
+                if !fields.is_empty() { return Err(InterpreterError::PositionalAfterField) }
+
+                positional.push(item.clone());
+            },
+        }
+    }
+
+    Ok((positional, fields))
+}
+
+/// Match positional items one by one.
+///
+/// Optional items of the reference may be skipped, in which case their defaults are bound,
+/// and a trailing rest pattern captures all remaining items of the given sequence.
+fn linearize_positional(reference: &[Pattern], given: &[Pattern]) -> MatchResult {
+    match reference.split_first() {
+        None => {
+            if given.is_empty() {
+                Ok(Environment::empty())
+            } else {
+                Err(InterpreterError::NoMatch)
+            }
+        },
+
+        Some((Pattern::Rest(rest), tail)) => {
+            // Only trailing rest patterns are supported.
+            if !tail.is_empty() { return Err(InterpreterError::NoMatch) }
+
+            self::bind_rest(rest, given.to_vec())
+        },
+
+        Some((head, tail)) => {
+            if let Some((first, others)) = given.split_first() {
+                if let Ok(head_map) = head.linearize(first.clone()) {
+                    if let Ok(tail_map) = self::linearize_positional(tail, others) {
+                        if let Ok(env) = head_map.merge(tail_map) {
+                            return Ok(env)
+                        }
+                    }
+                }
+            }
+
+            if head.is_optional() {
+                head.defaults()?.merge(self::linearize_positional(tail, given)?)
+            } else {
+                Err(InterpreterError::NoMatch)
+            }
+        },
+    }
+}
+
+/// Match fields by their names, binding the defaults of any optional fields which are missing.
+fn linearize_fields(reference: &[FieldPattern], given: Vec<FieldPattern>) -> MatchResult {
+    let mut given_names  = vec![];
+    let mut given_values = HashMap::new();
+
+    for field in given {
+        if given_values.insert(field.name.clone(), *field.value).is_some() {
+            return Err(InterpreterError::DuplicateField { name: field.name })
         }
+
+        given_names.push(field.name);
     }
-}
\ No newline at end of file
+
+    let mut reference_names = vec![];
+    let mut env = Environment::empty();
+
+    for field in reference {
+        if reference_names.contains(&field.name) {
+            return Err(InterpreterError::DuplicateField { name: field.name.clone() })
+        }
+
+        reference_names.push(field.name.clone());
+
+        let field_map = match given_values.remove(&field.name) {
+            Some(value) => field.value.linearize(value)?,
+
+            None => {
+                if field.default.is_none() {
+                    return Err(InterpreterError::MissingField { name: field.name.clone() })
+                }
+
+                Pattern::Field(field.clone()).defaults()?
+            },
+        };
+
+        env = env.merge(field_map)?;
+    }
+
+    // Report the first field in the given order which the reference does not know about.
+    if let Some(name) = given_names.into_iter().find(|name| given_values.contains_key(name)) {
+        return Err(InterpreterError::UnknownField { name })
+    }
+
+    Ok(env)
+}
+
+/// Bind the given items to the name of a rest pattern as a list.
+fn bind_rest(reference: &RestPattern, items: Vec<Pattern>) -> MatchResult {
+    let mut variables = HashMap::new();
+
+    if let Some(name) = &reference.name {
+        let list = Obj::list(items.into_iter().map(self::into_obj).collect());
+
+        variables.insert(VariablePattern::from_name(name.clone()), Box::new(list));
+    }
+
+    Ok(Environment::from(variables))
+}
+
+/// Unwrap a structured value, like a tuple stored in a variable, so it can be destructured.
+///
+/// Error values are destructured as a tuple of their kind, message and payload.
+fn destructure(pattern: Pattern) -> Pattern {
+    if let Pattern::Value(ValuePattern { obj }) = &pattern {
+        match &obj.kind {
+            ObjKind::Pattern(inner) => return inner.clone(),
+            ObjKind::Error(error)   => return error.to_pattern(),
+            _                       => (),
+        }
+    }
+
+    pattern
+}
+
+/// Convert a given pattern into the runtime value it represents.
+fn into_obj(pattern: Pattern) -> Obj {
+    match pattern {
+        Pattern::Value(ValuePattern { obj }) => *obj,
+        Pattern::List(list)                  => Obj::list(list.items().into_iter().map(self::into_obj).collect()),
+
+        pattern => Obj::new(ObjKind::Pattern(pattern)),
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Alternative(pattern) => write!(f, "{}", pattern),
+            Pattern::Field(pattern)       => write!(f, "{}", pattern),
+            Pattern::List(pattern)        => write!(f, "{}", pattern),
+            Pattern::Named(pattern)       => write!(f, "{}", pattern),
+            Pattern::Pair(pattern)        => write!(f, "{}", pattern),
+            Pattern::Range(pattern)       => write!(f, "{}", pattern),
+            Pattern::Rest(pattern)        => write!(f, "{}", pattern),
+            Pattern::Tuple(pattern)       => write!(f, "{}", pattern),
+            Pattern::Value(pattern)       => write!(f, "{}", pattern),
+            Pattern::Variable(pattern)    => write!(f, "{}", pattern),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str) -> Pattern {
+        Pattern::Variable(VariablePattern::from_name(name.to_string()))
+    }
+
+    fn int(n: i64) -> Pattern {
+        Pattern::Value(ValuePattern { obj: Box::new(Obj::new(ObjKind::Int(n))) })
+    }
+
+    fn pair(left: Pattern, right: Pattern) -> Pattern {
+        Pattern::Pair(PairPattern { left: Box::new(left), right: Box::new(right) })
+    }
+
+    #[test]
+    fn malformed_literals_inside_patterns_are_an_error() {
+        let source = Source::new("[yes]");
+
+        let literal = Expression {
+            kind:      ExpressionKind::Literal(magc::types::Literal::Boolean),
+            start_pos: 1,
+            end_pos:   4,
+        };
+
+        // A list of values in a pattern is converted item by item, like `[yes]`.
+        let list = MagcPattern::Value(magc::types::ValuePattern {
+            expression: Box::new(Expression {
+                kind:      ExpressionKind::List(Some(Box::new(literal))),
+                start_pos: 0,
+                end_pos:   5,
+            }),
+        });
+
+        assert_eq!(
+            Pattern::from_magc(list, &source),
+            Err(InterpreterError::InvalidLiteral {
+                expected: String::from("Boolean"),
+                lexeme:   String::from("yes"),
+            }),
+        );
+    }
+
+    #[test]
+    fn repeated_names_match_equal_values() {
+        let env = pair(variable("x"), variable("x"))
+            .linearize(pair(int(1), int(1)))
+            .unwrap();
+
+        assert_eq!(
+            env.entries.get(&VariablePattern::from_name("x".to_string())),
+            Some(&Box::new(Obj::new(ObjKind::Int(1)))),
+        );
+    }
+
+    #[test]
+    fn repeated_names_reject_different_values() {
+        let result = pair(variable("x"), variable("x")).linearize(pair(int(1), int(2)));
+
+        assert_eq!(result.err(), Some(InterpreterError::NoMatch));
+    }
+
+    #[test]
+    fn check_bindings_reports_repeated_names() {
+        assert_eq!(
+            pair(variable("x"), pair(variable("y"), variable("x"))).check_bindings(),
+            Err(InterpreterError::DuplicateBinding { name: "x".to_string() }),
+        );
+
+        assert_eq!(pair(variable("x"), variable("y")).check_bindings(), Ok(()));
+    }
+
+    #[test]
+    fn check_bindings_counts_alternatives_once() {
+        let alternative = AlternativePattern::new(vec![variable("x"), variable("x")]).unwrap();
+
+        assert_eq!(Pattern::Alternative(alternative).check_bindings(), Ok(()));
+    }
+
+    fn field(name: &str, value: Pattern, default: Option<Obj>) -> Pattern {
+        Pattern::Field(FieldPattern {
+            name:    name.to_string(),
+            value:   Box::new(value),
+            default: default.map(Box::new),
+        })
+    }
+
+    #[test]
+    fn fields_with_defaults_are_optional() {
+        let optional = || field("retries", variable("n"), Some(Obj::new(ObjKind::Int(3))));
+        let required = || field("name", variable("name"), None);
+
+        assert!(optional().is_optional());
+        assert!(!required().is_optional());
+
+        assert!(pair(optional(), Pattern::Rest(RestPattern { name: None })).is_optional());
+        assert!(!pair(optional(), required()).is_optional());
+        assert!(!variable("x").is_optional());
+    }
+
+    #[test]
+    fn missing_optional_fields_bind_their_default() {
+        let signature = pair(variable("x"), field("retries", variable("n"), Some(Obj::new(ObjKind::Int(3)))));
+
+        let env = signature.linearize(int(1)).unwrap();
+
+        assert_eq!(
+            env.entries.get(&VariablePattern::from_name("n".to_string())),
+            Some(&Box::new(Obj::new(ObjKind::Int(3)))),
+        );
+    }
+
+    #[test]
+    fn evaluate_defaults_evaluates_nested_defaults_in_scope() {
+        let mut interpreter = Interpreter::new();
+        interpreter.define_variable(VariablePattern::from_name("base".to_string()), Obj::new(ObjKind::Int(3))).unwrap();
+
+        let reference = Obj::new(ObjKind::Pattern(variable("base")));
+        let signature = Pattern::Tuple(TuplePattern {
+            child: Box::new(pair(variable("x"), field("retries", variable("n"), Some(reference)))),
+        });
+
+        let evaluated = signature.evaluate_defaults(&mut interpreter, None).unwrap();
+
+        assert_eq!(
+            evaluated,
+            Pattern::Tuple(TuplePattern {
+                child: Box::new(pair(variable("x"), field("retries", variable("n"), Some(Obj::new(ObjKind::Int(3)))))),
+            }),
+        );
+    }
+
+    fn rest(name: &str) -> Pattern {
+        Pattern::Rest(RestPattern { name: Some(name.to_string()) })
+    }
+
+    fn list_of(items: &[i64]) -> Box<Obj> {
+        Box::new(Obj::list(items.iter().map(|n| Obj::new(ObjKind::Int(*n))).collect()))
+    }
+
+    #[test]
+    fn trailing_rest_patterns_capture_the_remaining_items() {
+        let signature = pair(variable("x"), rest("rest"));
+
+        let env = signature.linearize(pair(int(1), pair(int(2), int(3)))).unwrap();
+
+        assert_eq!(env.entries.get(&VariablePattern::from_name("x".to_string())), Some(&Box::new(Obj::new(ObjKind::Int(1)))));
+        assert_eq!(env.entries.get(&VariablePattern::from_name("rest".to_string())), Some(&list_of(&[2, 3])));
+
+        let env = signature.linearize(int(1)).unwrap();
+
+        assert_eq!(env.entries.get(&VariablePattern::from_name("rest".to_string())), Some(&list_of(&[])));
+    }
+
+    #[test]
+    fn rest_patterns_in_the_middle_never_match() {
+        let signature = pair(variable("x"), pair(rest("rest"), variable("y")));
+
+        assert_eq!(
+            signature.linearize(pair(int(1), pair(int(2), int(3)))).err(),
+            Some(InterpreterError::NoMatch),
+        );
+    }
+
+    fn list(items: Vec<Pattern>) -> Pattern {
+        let child = items.into_iter().rev().reduce(|right, left| pair(left, right));
+
+        Pattern::List(ListPattern { child: child.map(Box::new) })
+    }
+
+    fn list_value(items: &[i64]) -> Pattern {
+        Pattern::Value(ValuePattern { obj: list_of(items) })
+    }
+
+    #[test]
+    fn head_and_tail_patterns_split_lists() {
+        let head_tail = list(vec![variable("head"), rest("tail")]);
+
+        let env = head_tail.linearize(list_value(&[1, 2, 3])).unwrap();
+
+        assert_eq!(env.entries.get(&VariablePattern::from_name("head".to_string())), Some(&Box::new(Obj::new(ObjKind::Int(1)))));
+        assert_eq!(env.entries.get(&VariablePattern::from_name("tail".to_string())), Some(&list_of(&[2, 3])));
+
+        let env = head_tail.linearize(list_value(&[1])).unwrap();
+
+        assert_eq!(env.entries.get(&VariablePattern::from_name("tail".to_string())), Some(&list_of(&[])));
+
+        assert_eq!(head_tail.linearize(list_value(&[])).err(), Some(InterpreterError::NoMatch));
+    }
+
+    #[test]
+    fn list_patterns_match_exact_lengths() {
+        let empty  = list(vec![]);
+        let single = list(vec![variable("x")]);
+
+        assert!(empty.matches_with(list_value(&[])));
+        assert!(!empty.matches_with(list_value(&[1])));
+
+        assert!(single.matches_with(list_value(&[1])));
+        assert!(!single.matches_with(list_value(&[])));
+        assert!(!single.matches_with(list_value(&[1, 2])));
+
+        // Lists given as patterns are matched in the same way as list values.
+        assert!(single.matches_with(list(vec![int(1)])));
+        assert!(!int(1).matches_with(list_value(&[1])));
+    }
+
+    #[test]
+    fn named_patterns_destructure_values_stored_in_variables() {
+        let point = Pattern::Named(NamedPattern {
+            name:    "p".to_string(),
+            pattern: Box::new(Pattern::Tuple(TuplePattern { child: Box::new(pair(variable("x"), variable("y"))) })),
+        });
+
+        // A tuple which was bound to a variable earlier is passed on as a value.
+        let tuple = Obj::new(ObjKind::Pattern(Pattern::Tuple(TuplePattern { child: Box::new(pair(int(1), int(2))) })));
+        let env = point.linearize(Pattern::Value(ValuePattern { obj: Box::new(tuple.clone()) })).unwrap();
+
+        assert_eq!(env.entries.get(&VariablePattern::from_name("p".to_string())), Some(&Box::new(tuple)));
+        assert_eq!(env.entries.get(&VariablePattern::from_name("x".to_string())), Some(&Box::new(Obj::new(ObjKind::Int(1)))));
+        assert_eq!(env.entries.get(&VariablePattern::from_name("y".to_string())), Some(&Box::new(Obj::new(ObjKind::Int(2)))));
+
+        assert!(!point.matches_with(int(1)));
+    }
+
+    fn range(start: Option<ObjKind>, end: Option<ObjKind>, inclusive: bool) -> Pattern {
+        Pattern::Range(RangePattern {
+            start: start.map(|kind| Box::new(Obj::new(kind))),
+            end:   end.map(|kind| Box::new(Obj::new(kind))),
+            inclusive,
+        })
+    }
+
+    fn value(kind: ObjKind) -> Pattern {
+        Pattern::Value(ValuePattern { obj: Box::new(Obj::new(kind)) })
+    }
+
+    #[test]
+    fn ranges_match_numbers_between_their_bounds() {
+        let digits = range(Some(ObjKind::Int(0)), Some(ObjKind::Int(10)), false);
+
+        assert!(digits.matches_with(int(0)));
+        assert!(digits.matches_with(value(ObjKind::Float(String::from("9.5")))));
+        assert!(digits.matches_with(value(ObjKind::UInt(9))));
+        assert!(!digits.matches_with(int(10)));
+        assert!(!digits.matches_with(int(-1)));
+
+        assert!(range(Some(ObjKind::Int(0)), Some(ObjKind::Int(10)), true).matches_with(int(10)));
+        assert!(range(None, Some(ObjKind::Int(0)), false).matches_with(int(i64::MIN)));
+        assert!(range(Some(ObjKind::Int(0)), None, false).matches_with(value(ObjKind::UInt(u64::MAX))));
+    }
+
+    #[test]
+    fn ranges_match_characters_but_not_other_values() {
+        let char = |c: &str| ObjKind::String(c.to_string());
+        let lowercase = range(Some(char("a")), Some(char("z")), true);
+
+        assert!(lowercase.matches_with(value(char("q"))));
+        assert!(!lowercase.matches_with(value(char("Q"))));
+        assert!(!lowercase.matches_with(value(char("ab"))));
+        assert!(!lowercase.matches_with(int(1)));
+        assert!(!lowercase.matches_with(value(ObjKind::Boolean(true))));
+    }
+}
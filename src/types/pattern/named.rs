@@ -0,0 +1,14 @@
+use super::Pattern;
+
+/// A pattern which binds the whole value as well as its parts, like `p @ (x, y)`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NamedPattern {
+    pub name:    String,
+    pub pattern: Box<Pattern>,
+}
+
+impl std::fmt::Display for NamedPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} @ {}", self.name, self.pattern)
+    }
+}
@@ -5,4 +5,10 @@ use super::Pattern;
 pub struct PairPattern {
     pub left: Box<Pattern>,
     pub right: Box<Pattern>,
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for PairPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.left, self.right)
+    }
+}
@@ -0,0 +1,61 @@
+use std::cmp::Ordering;
+
+use crate::types::{Obj, ObjKind};
+use crate::types::obj::compare;
+
+/// A range of numbers or characters, like `0..10` or `'a'..='z'`.
+///
+/// Either bound may be left out to create a range which is open on that side.
+/// Numbers of different types are compared using the usual numeric promotion rules.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RangePattern {
+    pub start:     Option<Box<Obj>>,
+    pub end:       Option<Box<Obj>>,
+    /// Whether the end of the range is part of it.
+    pub inclusive: bool,
+}
+
+impl RangePattern {
+    /// Return whether the given value lies within this range.
+    ///
+    /// Values which cannot be compared with the bounds are never contained in the range.
+    pub fn contains(&self, obj: &Obj) -> bool {
+        let after_start = match &self.start {
+            Some(start) => matches!(compare(obj, start), Ok(Ordering::Greater | Ordering::Equal)),
+            None        => true,
+        };
+
+        let before_end = match &self.end {
+            Some(end) if self.inclusive => matches!(compare(obj, end), Ok(Ordering::Less | Ordering::Equal)),
+            Some(end)                   => matches!(compare(obj, end), Ok(Ordering::Less)),
+            None                        => true,
+        };
+
+        after_start && before_end
+    }
+}
+
+impl std::fmt::Display for RangePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(start) = &self.start {
+            self::write_bound(f, start)?;
+        }
+
+        write!(f, "{}", if self.inclusive { "..=" } else { ".." })?;
+
+        if let Some(end) = &self.end {
+            self::write_bound(f, end)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a bound of a range, using character syntax for strings with a single character.
+fn write_bound(f: &mut std::fmt::Formatter<'_>, bound: &Obj) -> std::fmt::Result {
+    match &bound.kind {
+        ObjKind::String(s) if s.chars().count() == 1 => write!(f, "'{}'", s.escape_default()),
+
+        _ => write!(f, "{}", bound),
+    }
+}
@@ -0,0 +1,14 @@
+/// A pattern which captures the remaining items of a pair chain, like `...rest`.
+///
+/// The captured items are bound as a list. If the pattern has no name, the items
+/// are matched but no destructuring will take place.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RestPattern {
+    pub name: Option<String>,
+}
+
+impl std::fmt::Display for RestPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "...{}", self.name.as_deref().unwrap_or("_"))
+    }
+}
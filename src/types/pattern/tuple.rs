@@ -4,4 +4,10 @@ use super::Pattern;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TuplePattern {
     pub child: Box<Pattern>,
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for TuplePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({})", self.child)
+    }
+}
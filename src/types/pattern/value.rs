@@ -4,4 +4,10 @@ use crate::types::Obj;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ValuePattern {
     pub obj: Box<Obj>,
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for ValuePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.obj)
+    }
+}
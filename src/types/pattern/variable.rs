@@ -15,4 +15,16 @@ impl VariablePattern {
             type_id: None,
         }
     }
-}
\ No newline at end of file
+}
+
+impl std::fmt::Display for VariablePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name.as_deref().unwrap_or("_"))?;
+
+        if let Some(type_id) = &self.type_id {
+            write!(f, " {}", type_id)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//! Expressions which introduce and change variables.
+
+use crate::types::{Obj, Pattern};
+
+/// A definition of one or more variables, like `let (x, y) = value`.
+///
+/// The value is matched against the pattern and every variable bound by the
+/// pattern is defined in the innermost scope. Variables are immutable unless
+/// the definition is marked as mutable, like `let mut x = value`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Definition {
+    pub pattern: Pattern,
+    pub value:   Box<Obj>,
+    pub mutable: bool,
+}
+
+/// An assignment of a new value to an existing variable, like `x = value`.
+///
+/// The variable is looked up starting from the innermost scope, so the
+/// nearest enclosing definition is the one which changes. It has to be mutable.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Assignment {
+    pub name:  String,
+    pub value: Box<Obj>,
+}
+
+/// A list of expressions evaluated in order inside of a new scope.
+///
+/// Variables defined by the expressions are only visible until the end of the
+/// sequence, whose value is the value of its last expression.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Sequence {
+    pub expressions: Vec<Obj>,
+}
+
+impl Definition {
+    pub fn new(pattern: Pattern, value: Box<Obj>) -> Self {
+        Self {
+            pattern,
+            value,
+            mutable: false,
+        }
+    }
+
+    pub fn mutable(pattern: Pattern, value: Box<Obj>) -> Self {
+        Self {
+            pattern,
+            value,
+            mutable: true,
+        }
+    }
+}
+
+impl Assignment {
+    pub fn new(name: String, value: Box<Obj>) -> Self {
+        Self {
+            name,
+            value,
+        }
+    }
+}
+
+impl Sequence {
+    pub fn new(expressions: Vec<Obj>) -> Self {
+        Self {
+            expressions,
+        }
+    }
+}